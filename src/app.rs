@@ -1,9 +1,24 @@
 use std::fs;
-use std::path::Path;
+use std::path::PathBuf;
 use std::time::Instant;
-use crate::config::DstlConfig;
+use crate::config::{CustomEntry, DstlConfig};
+use crate::desktop;
+use crate::history::History;
+use crate::launch;
+use crate::line_editor::LineEditor;
 use fuzzy_matcher::FuzzyMatcher;
 use fuzzy_matcher::skim::SkimMatcherV2;
+use ratatui::layout::Rect;
+
+/// Quality of a prefix match in a search field
+const PREFIX_QUALITY: i64 = 1000;
+/// Best quality of a fuzzy match, which always ranks below a prefix match
+const FUZZY_QUALITY: i64 = PREFIX_QUALITY / 2;
+/// Score step per 1.0 of field weight. Weights 0.1 apart differ by exactly
+/// PREFIX_QUALITY, so a prefix match in the lower field can tie a weak fuzzy
+/// match in the higher one and the frecency bonus decides; fields 0.2 or more
+/// apart keep their order unless `frecency_weight` is very large
+const FIELD_RANK: f64 = 10_000.0;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Focus {
@@ -20,47 +35,136 @@ pub enum Mode {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SinglePaneMode {
-    Dmenu,       // load apps from PATH (dmenu style)
+    Dmenu,       // read candidates from stdin and print the selection
+    Run,         // load apps from PATH
     DesktopApps, // load .desktop apps
 }
 
+/// Editing mode of `dstl.vim_mode`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VimMode {
+    /// Keys type into the query
+    Insert,
+    /// Keys navigate and edit
+    Normal,
+}
+
+impl VimMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            VimMode::Insert => "INSERT",
+            VimMode::Normal => "NORMAL",
+        }
+    }
+}
+
+/// A search query prepared once per search instead of once per field
+pub struct SearchQuery {
+    text: String,
+    lower: String,
+    /// Fuzzy score of the query matched against itself, which field scores are scaled to
+    perfect: i64,
+}
+
+/// Where the last frame drew a list, for mouse hit-testing
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ListArea {
+    pub area: Rect,
+    /// Index of the first visible row
+    pub offset: usize,
+}
+
+/// Clickable parts of the last frame
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScreenAreas {
+    pub search: Rect,
+    pub categories: Option<ListArea>,
+    /// The app list, or the action menu drawn in its place
+    pub apps: ListArea,
+}
+
 pub struct App {
     pub mode: Mode,
     pub single_pane_mode: SinglePaneMode,
     pub should_quit: bool,
-    pub search_query: String,
-    pub cursor_position: usize,
+    pub query: LineEditor,
     pub cursor_visible: bool,
     pub cursor_last_toggle: std::time::Instant,
     pub categories: Vec<String>,
     pub apps: Vec<AppEntry>,
     pub recent_apps: Vec<String>,
+    pub history: History,
     pub selected_category: usize,
     pub selected_app: usize,
     pub focus: Focus,
-    pub app_to_launch: Option<String>,
+    pub app_to_launch: Option<AppEntry>,
+    pub action_to_launch: Option<DesktopAction>,
+    /// App whose desktop actions are listed in place of the apps, if any
+    pub action_menu: Option<AppEntry>,
+    pub selected_action: usize,
+    /// Listing the hidden entries so they can be restored
+    pub show_hidden: bool,
+    /// Whether the detail pane is open
+    pub show_details: bool,
+    pub areas: ScreenAreas,
+    /// Time and row of the last click in the app list, to spot double-clicks
+    pub last_click: Option<(Instant, usize)>,
+    /// Current vim mode, `None` unless `vim_mode` is on
+    pub vim_mode: Option<VimMode>,
+    /// First key of a two-key normal-mode command (`gg`, `dd`)
+    pub vim_pending: Option<char>,
+    /// Index into `history.queries` of the search being shown by recall
+    pub query_recall: Option<usize>,
+    /// What was typed before recall started, restored when stepping past the newest search
+    pub query_draft: String,
     pub config: DstlConfig,
+    /// Dual-pane category counts, kept until something they depend on changes
+    category_counts: Option<CategoryCounts>,
     fuzzy_matcher: SkimMatcherV2,
 }
 
+/// Matching apps per entry of `App::categories`, and the state they were counted in
+#[derive(Debug, Clone, PartialEq)]
+struct CategoryCounts {
+    query: String,
+    show_hidden: bool,
+    recent: Vec<String>,
+    pinned: Vec<String>,
+    hidden: Vec<String>,
+    categories: Vec<String>,
+    counts: Vec<usize>,
+}
+
 impl Clone for App {
     fn clone(&self) -> Self {
         Self {
             mode: self.mode,
             single_pane_mode: self.single_pane_mode,
             should_quit: self.should_quit,
-            search_query: self.search_query.clone(),
-            cursor_position: self.cursor_position,
+            query: self.query.clone(),
             cursor_visible: true,
             cursor_last_toggle: Instant::now(),
             categories: self.categories.clone(),
             apps: self.apps.clone(),
             recent_apps: self.recent_apps.clone(),
+            history: self.history.clone(),
             selected_category: self.selected_category,
             selected_app: self.selected_app,
             focus: self.focus,
             app_to_launch: self.app_to_launch.clone(),
+            action_to_launch: self.action_to_launch.clone(),
+            action_menu: self.action_menu.clone(),
+            selected_action: self.selected_action,
+            show_hidden: self.show_hidden,
+            show_details: self.show_details,
+            areas: self.areas,
+            last_click: self.last_click,
+            vim_mode: self.vim_mode,
+            vim_pending: self.vim_pending,
+            query_recall: self.query_recall,
+            query_draft: self.query_draft.clone(),
             config: self.config.clone(),
+            category_counts: self.category_counts.clone(),
             fuzzy_matcher: SkimMatcherV2::default(),
         }
     }
@@ -72,18 +176,30 @@ impl std::fmt::Debug for App {
             .field("mode", &self.mode)
             .field("single_pane_mode", &self.single_pane_mode)
             .field("should_quit", &self.should_quit)
-            .field("search_query", &self.search_query)
-            .field("cursor_position", &self.cursor_position)
+            .field("query", &self.query)
             .field("cursor_visible", &self.cursor_visible)
             .field("cursor_last_toggle", &self.cursor_last_toggle)
             .field("categories", &self.categories)
             .field("apps", &self.apps)
             .field("recent_apps", &self.recent_apps)
+            .field("history", &self.history)
             .field("selected_category", &self.selected_category)
             .field("selected_app", &self.selected_app)
             .field("focus", &self.focus)
             .field("app_to_launch", &self.app_to_launch)
+            .field("action_to_launch", &self.action_to_launch)
+            .field("action_menu", &self.action_menu)
+            .field("selected_action", &self.selected_action)
+            .field("show_hidden", &self.show_hidden)
+            .field("show_details", &self.show_details)
+            .field("areas", &self.areas)
+            .field("last_click", &self.last_click)
+            .field("vim_mode", &self.vim_mode)
+            .field("vim_pending", &self.vim_pending)
+            .field("query_recall", &self.query_recall)
+            .field("query_draft", &self.query_draft)
             .field("config", &self.config)
+            .field("category_counts", &self.category_counts)
             .field("fuzzy_matcher", &"SkimMatcherV2")
             .finish()
     }
@@ -91,14 +207,119 @@ impl std::fmt::Debug for App {
 
 #[derive(Debug, Clone)]
 pub struct AppEntry {
+    /// Stable key for history: the desktop-file ID, or the absolute path in run mode
+    pub id: String,
     pub name: String,
+    /// Main dual-pane category
     pub category: String,
+    /// Every dual-pane category the entry is listed under, `category` first
+    pub groups: Vec<String>,
+    /// Raw freedesktop `Categories=` values
+    pub categories: Vec<String>,
+    /// The .desktop file the entry came from
+    pub desktop_file: Option<PathBuf>,
+    /// Command line for display and `--print-only`
     pub exec: String,
+    /// Parsed Exec, spawned directly; empty means `exec` is a shell command
+    pub argv: Vec<String>,
+    /// Variables from an `env FOO=bar cmd` Exec prefix
+    pub env: Vec<(String, String)>,
+    /// Working directory from `Path=`
+    pub working_dir: Option<PathBuf>,
     pub terminal: bool,
+    /// `terminal` was set on purpose (custom entries), so it's never guessed
+    pub terminal_explicit: bool,
+    pub generic_name: Option<String>,
+    pub comment: Option<String>,
+    pub keywords: Vec<String>,
+    pub actions: Vec<DesktopAction>,
+    /// Glyph from a custom entry's `icon`, drawn before the name
+    pub glyph: Option<String>,
+}
+
+/// A `[Desktop Action ...]` group, e.g. Firefox's "New Private Window"
+#[derive(Debug, Clone)]
+pub struct DesktopAction {
+    pub name: String,
+    pub exec: String,
+    pub argv: Vec<String>,
+    pub env: Vec<(String, String)>,
 }
 
 impl AppEntry {
+    /// A plain dmenu candidate
+    pub fn from_line(line: String) -> Self {
+        Self {
+            id: line.clone(),
+            name: line.clone(),
+            category: "Dmenu".to_string(),
+            groups: vec!["Dmenu".to_string()],
+            categories: Vec::new(),
+            desktop_file: None,
+            exec: line,
+            argv: Vec::new(),
+            env: Vec::new(),
+            working_dir: None,
+            terminal: false,
+            terminal_explicit: false,
+            generic_name: None,
+            comment: None,
+            keywords: Vec::new(),
+            actions: Vec::new(),
+            glyph: None,
+        }
+    }
+
+    /// An entry from `dstl.entries`, run through the shell
+    pub fn from_custom(custom: &CustomEntry) -> Self {
+        Self {
+            id: format!("custom:{}", custom.key),
+            name: custom.name.clone(),
+            category: custom.category.clone(),
+            groups: vec![custom.category.clone()],
+            categories: Vec::new(),
+            desktop_file: None,
+            exec: custom.exec.clone(),
+            argv: Vec::new(),
+            env: Vec::new(),
+            working_dir: None,
+            terminal: custom.terminal,
+            terminal_explicit: true,
+            generic_name: None,
+            comment: custom.comment.clone(),
+            keywords: custom.keywords.clone(),
+            actions: Vec::new(),
+            glyph: custom.icon.clone(),
+        }
+    }
+
+    /// This entry, but launching one of its desktop actions instead
+    pub fn with_action(&self, action: &DesktopAction) -> Self {
+        Self {
+            name: action.name.clone(),
+            exec: action.exec.clone(),
+            argv: action.argv.clone(),
+            env: action.env.clone(),
+            actions: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    /// Basename of the program in Exec (`/usr/bin/foot --server` → `foot`)
+    pub fn exec_name(&self) -> Option<&str> {
+        let program = match self.argv.first() {
+            Some(program) => program.as_str(),
+            None => self.exec.split_whitespace().next()?,
+        };
+        program.rsplit('/').next()
+    }
+
+    /// Guess whether a command without `Terminal=true` still wants a terminal.
+    /// Entries that said so explicitly, like custom ones, are never guessed.
     pub fn needs_terminal(&self) -> bool {
+        if self.terminal_explicit {
+            return false;
+        }
         self.category == "CLI"
             || self.exec.contains("bash")
             || self.exec.contains("sh ")
@@ -112,120 +333,199 @@ impl AppEntry {
 impl App {
     /// Initialize the app with specified single pane mode and start mode
     pub fn new(single_pane_mode: SinglePaneMode, start_mode: Mode, config: &DstlConfig) -> Self {
-        let (categories, apps, mode, focus) = match start_mode {
-            Mode::SinglePane => {
-                let (cats, apps) = Self::load_for_mode(single_pane_mode);
-                (cats, apps, Mode::SinglePane, Focus::Search)
-            }
-            Mode::DualPane => {
-                let (cats, apps) = Self::load_desktop_apps();
-                (cats, apps, Mode::DualPane, Focus::Search)
-            }
+        let (categories, apps) = match start_mode {
+            Mode::SinglePane => Self::load_for_mode(single_pane_mode, config),
+            Mode::DualPane => Self::load_desktop_apps(config),
         };
 
-        let mut app = Self {
+        let mut app = Self::with_apps(single_pane_mode, start_mode, categories, apps, config);
+
+        // Load usage history from disk (dmenu candidates are not apps)
+        if single_pane_mode != SinglePaneMode::Dmenu {
+            app.history = History::load();
+            app.migrate_legacy_history();
+            app.refresh_recent();
+            app.sync_favorites();
+        }
+
+        app
+    }
+
+    /// An app listing `apps` under `categories`, with an empty history and the search bar focused
+    fn with_apps(
+        single_pane_mode: SinglePaneMode,
+        mode: Mode,
+        categories: Vec<String>,
+        apps: Vec<AppEntry>,
+        config: &DstlConfig,
+    ) -> Self {
+        Self {
             mode,
             single_pane_mode,
             should_quit: false,
-            search_query: String::new(),
-            cursor_position: 0,
+            query: LineEditor::default(),
             cursor_visible: true,
             cursor_last_toggle: Instant::now(),
             categories,
             apps,
             recent_apps: Vec::new(),
+            history: History::default(),
             selected_category: 0,
             selected_app: 0,
-            focus,
+            focus: Focus::Search,
             app_to_launch: None,
+            action_to_launch: None,
+            action_menu: None,
+            selected_action: 0,
+            show_hidden: false,
+            show_details: config.show_details,
+            areas: ScreenAreas::default(),
+            last_click: None,
+            vim_mode: config.vim_mode.then_some(VimMode::Insert),
+            vim_pending: None,
+            query_recall: None,
+            query_draft: String::new(),
             config: config.clone(),
+            category_counts: None,
             fuzzy_matcher: SkimMatcherV2::default(),
+        }
+    }
+
+    /// Record a launch, and the search it came from, in the usage database
+    /// and refresh the Recent list
+    pub fn add_to_recent(&mut self, app_id: &str) {
+        self.history.record(app_id);
+        self.history.record_query(self.query.text(), app_id);
+        self.refresh_recent();
+
+        // Save to disk
+        let _ = self.history.save();
+    }
+
+    /// Import the old name-keyed ~/.cache/dstl history the first time we run
+    fn migrate_legacy_history(&mut self) {
+        if !self.history.needs_migration() {
+            return;
+        }
+
+        // Old history only knew desktop apps by display name
+        let desktop_apps = if self.mode == Mode::DualPane || self.single_pane_mode == SinglePaneMode::DesktopApps {
+            self.apps.clone()
+        } else {
+            Self::load_desktop_apps(&self.config).1
         };
 
-        // Load recent apps from disk
-        let _ = app.load_recent();
+        self.history.migrate_legacy(|name| {
+            desktop_apps.iter().find(|a| a.name == name).map(|a| a.id.clone())
+        });
 
-        app
+        // Written even if nothing matched, so this only ever runs once
+        let _ = self.history.save();
     }
 
-    /// Add an app to the recent list
-    pub fn add_to_recent(&mut self, app_name: String) {
-        // Remove the app if it already exists (to avoid duplicates)
-        self.recent_apps.retain(|a| a != &app_name);
-        
-        // Add to the front of the list
-        self.recent_apps.insert(0, app_name);
-        
-        // Keep only the configured number of recent apps
-        let max_recent = self.config.max_recent_apps;
-        if self.recent_apps.len() > max_recent {
-            self.recent_apps.truncate(max_recent);
+    /// Rebuild `recent_apps` from the usage database, best frecency first.
+    /// The history is shared with run mode and outlives uninstalled apps, so only
+    /// IDs of listed, unhidden entries take up slots.
+    fn refresh_recent(&mut self) {
+        let mut recent = self.history.ranked();
+        recent.retain(|id| !self.history.is_hidden(id) && self.apps.iter().any(|a| a.id == *id));
+        recent.truncate(self.config.max_recent_apps);
+        self.recent_apps = recent;
+    }
+
+    /// Search score plus a frecency bonus, so often/recently used apps win ties.
+    /// The entry picked last time the same search was started (see `History::learned`)
+    /// always comes first.
+    pub fn search_score(&self, app: &AppEntry, query: &SearchQuery) -> Option<i64> {
+        let score = self.matches_search(app, query)?;
+        if query.text.is_empty() {
+            return Some(score);
+        }
+        if self.history.learned(&query.text) == Some(app.id.as_str()) {
+            return Some(i64::MAX);
         }
 
-        // Save to disk
-        let _ = self.save_recent();
+        // Logarithmic, so heavy use nudges results instead of burying better matches
+        let frecency = self.history.frecency(&app.id);
+        let bonus = (self.config.frecency_weight * 10.0 * frecency.ln_1p()) as i64;
+        Some(score.saturating_add(bonus))
     }
 
-    /// Save recent apps to disk
-    pub fn save_recent(&self) -> std::io::Result<()> {
-        let config_dir = dirs::cache_dir()
-            .map(|p| p.join("dstl"))
-            .unwrap();
-        
-        fs::create_dir_all(&config_dir)?;
-        let recent_file = config_dir.join("recent.json");
-        
-        let json = serde_json::to_string_pretty(&self.recent_apps)?;
-        fs::write(recent_file, json)?;
-        Ok(())
+    /// Best score first; equal scores, e.g. two prefix matches, go to the more used app.
+    /// Frecency reads the clock, so it's looked up once per entry to keep the order stable.
+    fn sort_matches(&self, matched: &mut [(&AppEntry, i64)]) {
+        matched.sort_by_cached_key(|&(app, score)| {
+            let frecency = (self.history.frecency(&app.id) * 1000.0) as i64;
+            std::cmp::Reverse((score, frecency))
+        });
     }
 
-    /// Load recent apps from disk
-    pub fn load_recent(&mut self) -> std::io::Result<()> {
-        let config_dir = dirs::cache_dir()
-            .map(|p| p.join("dstl"))
-            .unwrap_or_else(|| std::path::PathBuf::from("."));
-        
-        let recent_file = config_dir.join("recent.json");
-        
-        if recent_file.exists() {
-            let json = fs::read_to_string(recent_file)?;
-            self.recent_apps = serde_json::from_str(&json).unwrap_or_default();
+    /// Step through earlier searches, newest first; stepping forward past the
+    /// newest brings back what was typed before recall started
+    pub fn recall_query(&mut self, older: bool) {
+        let count = self.history.queries.len();
+
+        // Recall carries on only while the bar still shows the recalled search
+        let current = self.query_recall.filter(|&i| {
+            self.history.queries.get(i).is_some_and(|q| q.query == self.query.text())
+        });
+        if current.is_none() {
+            self.query_draft = self.query.text().to_string();
         }
-        Ok(())
+
+        let next = match (current, older) {
+            (None, true) if count > 0 => Some(0),
+            (Some(i), true) => Some((i + 1).min(count - 1)),
+            (Some(i), false) if i > 0 => Some(i - 1),
+            (Some(_), false) => None,
+            _ => return,
+        };
+
+        let text = match next {
+            Some(i) => self.history.queries[i].query.clone(),
+            None => self.query_draft.clone(),
+        };
+        self.query_recall = next;
+        self.query.set_text(&text);
+    }
+
+    /// Whether an app belongs in the current view: hidden apps only show up in the unhide view
+    fn is_listed(&self, app: &AppEntry) -> bool {
+        self.history.is_hidden(&app.id) == self.show_hidden
     }
 
     pub fn visible_apps(&self) -> Vec<&AppEntry> {
-        let query = &self.search_query;
+        let query = self.search_query();
+        let listed = self.apps.iter().filter(|a| self.is_listed(a));
 
         // Start with all apps
-        let mut apps: Vec<&AppEntry> = if query.is_empty() {
-            self.apps.iter().collect()
+        let mut apps: Vec<&AppEntry> = if query.text.is_empty() {
+            listed.collect()
         } else {
             // Fuzzy match when searching
-            let mut matched: Vec<(&AppEntry, i64)> = self.apps.iter()
-                .filter_map(|a| self.matches_search(&a.name, query).map(|score| (a, score)))
+            let mut matched: Vec<(&AppEntry, i64)> = listed
+                .filter_map(|a| self.search_score(a, &query).map(|score| (a, score)))
                 .collect();
-            matched.sort_by(|a, b| b.1.cmp(&a.1));
+            self.sort_matches(&mut matched);
             matched.into_iter().map(|(a, _)| a).collect()
         };
 
         // If recent_first and not searching, reorder
-        if self.search_query.is_empty() && self.config.recent_first && !self.recent_apps.is_empty() {
+        if self.query.text().is_empty() && self.config.recent_first && !self.recent_apps.is_empty() {
             let mut recent_list = Vec::new();
             let mut seen = std::collections::HashSet::new();
 
             // Add recent apps first (must exist in apps)
-            for recent_name in &self.recent_apps {
-                if let Some(app) = apps.iter().find(|a| a.name == *recent_name) {
+            for recent_id in &self.recent_apps {
+                if let Some(app) = apps.iter().find(|a| a.id == *recent_id) {
                     recent_list.push(*app);
-                    seen.insert(recent_name.clone());
+                    seen.insert(recent_id.clone());
                 }
             }
 
             // Add remaining apps
             for app in apps {
-                if !seen.contains(&app.name) {
+                if !seen.contains(&app.id) {
                     recent_list.push(app);
                 }
             }
@@ -233,9 +533,156 @@ impl App {
             apps = recent_list;
         }
 
+        // Optionally float pinned apps to the top (keeping the order within each group)
+        if self.config.pinned_first && !self.history.pinned.is_empty() {
+            apps.sort_by_key(|a| !self.history.is_pinned(&a.id));
+        }
+
         apps
     }
 
+    /// Apps listed under a dual-pane category, in browsing order
+    pub fn apps_in_category(&self, category: &str) -> Vec<&AppEntry> {
+        let by_id = |ids: &[String]| -> Vec<&AppEntry> {
+            ids.iter()
+                .filter_map(|id| self.apps.iter().find(|a| &a.id == id))
+                .collect()
+        };
+
+        let mut apps = match category {
+            "Recent" => by_id(&self.recent_apps),
+            "Favorites" => by_id(&self.history.pinned),
+            _ => self.apps.iter().filter(|a| a.groups.iter().any(|g| g == category)).collect(),
+        };
+        apps.retain(|a| self.is_listed(a));
+        apps
+    }
+
+    /// A category's apps that match the search query, best match first
+    pub fn visible_apps_in_category(&self, category: &str) -> Vec<&AppEntry> {
+        let query = self.search_query();
+        let mut matched: Vec<(&AppEntry, i64)> = self.apps_in_category(category)
+            .into_iter()
+            .filter_map(|a| self.search_score(a, &query).map(|score| (a, score)))
+            .collect();
+
+        if !query.text.is_empty() {
+            self.sort_matches(&mut matched);
+        }
+        matched.into_iter().map(|(a, _)| a).collect()
+    }
+
+    /// How many matching apps each category lists, indexed like `categories`.
+    /// Scoring every app is too slow for every frame, so the counts are only
+    /// redone when the query, the view, the history lists or the categories changed.
+    pub fn category_counts(&mut self) -> &[usize] {
+        let fresh = self.category_counts.as_ref().is_some_and(|c| {
+            c.query == self.query.text()
+                && c.show_hidden == self.show_hidden
+                && c.recent == self.recent_apps
+                && c.pinned == self.history.pinned
+                && c.hidden == self.history.hidden
+                && c.categories == self.categories
+        });
+
+        if !fresh {
+            let counts = self
+                .categories
+                .iter()
+                .map(|c| self.visible_apps_in_category(c).len())
+                .collect();
+            self.category_counts = Some(CategoryCounts {
+                query: self.query.text().to_string(),
+                show_hidden: self.show_hidden,
+                recent: self.recent_apps.clone(),
+                pinned: self.history.pinned.clone(),
+                hidden: self.history.hidden.clone(),
+                categories: self.categories.clone(),
+                counts,
+            });
+        }
+
+        self.category_counts.as_ref().map_or(&[], |c| &c.counts)
+    }
+
+    /// The entry under the selection in the current view
+    pub fn selected_entry(&self) -> Option<&AppEntry> {
+        match self.mode {
+            Mode::SinglePane => self.visible_apps().get(self.selected_app).copied(),
+            Mode::DualPane => {
+                let cat_name = self.categories.get(self.selected_category)?;
+                self.visible_apps_in_category(cat_name).get(self.selected_app).copied()
+            }
+        }
+    }
+
+    /// Indices of the categories that still have apps matching the search query
+    pub fn matching_category_indices(&self) -> Vec<usize> {
+        if self.query.text().is_empty() && !self.show_hidden {
+            return (0..self.categories.len()).collect();
+        }
+
+        let query = self.search_query();
+        self.categories
+            .iter()
+            .enumerate()
+            .filter(|(_, cat_name)| {
+                self.apps_in_category(cat_name)
+                    .into_iter()
+                    .any(|a| self.matches_search(a, &query).is_some())
+            })
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Pin or unpin an app in the Favorites category
+    pub fn toggle_pin(&mut self, app_id: &str) {
+        self.history.toggle_pin(app_id);
+        self.sync_favorites();
+        let _ = self.history.save();
+    }
+
+    /// List the Favorites category only while something is pinned, so an empty
+    /// one never opens first; the selection stays on the same category
+    fn sync_favorites(&mut self) {
+        // Only the desktop-app categories have Recent and Favorites
+        let Some(recent) = self.categories.iter().position(|c| c == "Recent") else {
+            return;
+        };
+        let listed = self.categories.iter().position(|c| c == "Favorites");
+
+        match (listed, self.history.pinned.is_empty()) {
+            (Some(idx), true) => {
+                self.categories.remove(idx);
+                if self.selected_category > idx {
+                    self.selected_category -= 1;
+                }
+            }
+            (None, false) => {
+                let idx = if self.config.favorites_first { recent } else { recent + 1 };
+                self.categories.insert(idx, "Favorites".to_string());
+                if self.selected_category >= idx {
+                    self.selected_category += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Hide an app from the lists, or restore it from the unhide view
+    pub fn toggle_hidden(&mut self, app_id: &str) {
+        self.history.toggle_hidden(app_id);
+        self.refresh_recent();
+        let _ = self.history.save();
+    }
+
+    /// Switch between the normal lists and the list of hidden apps
+    pub fn toggle_hidden_view(&mut self) {
+        self.show_hidden = !self.show_hidden;
+        self.selected_app = 0;
+        self.selected_category = self.matching_category_indices().first().copied().unwrap_or(0);
+    }
+
     pub fn update_cursor_blink(&mut self) {
         use std::time::Duration;
 
@@ -264,16 +711,17 @@ impl App {
     pub fn toggle_mode(&mut self) {
         match self.mode {
             Mode::SinglePane => {
-                let (categories, apps) = Self::load_desktop_apps();
+                let (categories, apps) = Self::load_desktop_apps(&self.config);
                 self.categories = categories;
                 self.apps = apps;
                 self.mode = Mode::DualPane;
+                self.sync_favorites();
                 
                 // Keep leftmost pane focused when switching to DualPane
                 self.focus = Focus::Categories;
             }
             Mode::DualPane => {
-                let (categories, apps) = Self::load_for_mode(self.single_pane_mode);
+                let (categories, apps) = Self::load_for_mode(self.single_pane_mode, &self.config);
                 self.categories = categories;
                 self.apps = apps;
                 self.mode = Mode::SinglePane;
@@ -283,55 +731,141 @@ impl App {
             }
         }
 
-        // Reset selection indexes
+        // Reset selection indexes; the apps were reloaded, so recount them
         self.selected_category = 0;
         self.selected_app = 0;
+        self.category_counts = None;
+        self.refresh_recent();
+    }
+
+    /// The current query, ready to score many apps against
+    pub fn search_query(&self) -> SearchQuery {
+        let text = self.query.text().to_string();
+        let lower = text.to_lowercase();
+        let perfect = self.fuzzy_matcher.fuzzy_match(&lower, &lower).unwrap_or(1).max(1);
+        SearchQuery { text, lower, perfect }
     }
 
-    /// Check if an app matches the search query using fuzzy matching (case-insensitive)
-    pub fn matches_search(&self, app_name: &str, query: &str) -> Option<i64> {
-        if query.is_empty() {
+    /// Check if an app matches the search query using fuzzy matching (case-insensitive).
+    ///
+    /// Name, GenericName, Keywords, Comment and the executable name are all
+    /// scored; each field ranks by its configured weight first and the quality
+    /// of its match second, and the best one wins.
+    pub fn matches_search(&self, app: &AppEntry, query: &SearchQuery) -> Option<i64> {
+        if query.text.is_empty() {
             return Some(0); // Empty query matches everything
         }
 
-        let app_name_lower = app_name.to_lowercase();
-        let query_lower = query.to_lowercase();
+        let weights = &self.config.search_weights;
+
+        let mut fields: Vec<(&str, f64)> = vec![(&app.name, weights.name)];
+        if let Some(ref generic_name) = app.generic_name {
+            fields.push((generic_name, weights.generic_name));
+        }
+        fields.extend(app.keywords.iter().map(|k| (k.as_str(), weights.keywords)));
+        if let Some(exec_name) = app.exec_name() {
+            fields.push((exec_name, weights.exec));
+        }
+
+        let mut best = fields
+            .into_iter()
+            .filter(|(_, weight)| *weight > 0.0)
+            .filter_map(|(text, weight)| {
+                self.score_field(text, query).map(|quality| Self::weighted(quality, weight))
+            })
+            .max();
+
+        // Comments are long prose, so only whole-word prefixes count there
+        if let Some(ref comment) = app.comment
+            && weights.comment > 0.0
+            && comment.to_lowercase().split_whitespace().any(|w| w.starts_with(&query.lower))
+        {
+            let score = Self::weighted(PREFIX_QUALITY, weights.comment);
+            best = best.max(Some(score));
+        }
+
+        best
+    }
+
+    /// Match quality of one field: exact prefix match first, fuzzy match otherwise
+    fn score_field(&self, text: &str, query: &SearchQuery) -> Option<i64> {
+        let text_lower = text.to_lowercase();
 
         // Exact prefix match gets highest priority
-        if app_name_lower.starts_with(&query_lower) {
-            return Some(i64::MAX); // Push to top
+        if text_lower.starts_with(&query.lower) {
+            return Some(PREFIX_QUALITY);
         }
 
-        // Fuzzy match otherwise
-        self.fuzzy_matcher.fuzzy_match(&app_name_lower, &query_lower)
+        // Fuzzy scores grow with the query length; scale them against a perfect
+        // match of the query so every field is scored on the same range
+        let score = self.fuzzy_matcher.fuzzy_match(&text_lower, &query.lower)?;
+        Some((score * FUZZY_QUALITY / query.perfect).clamp(0, FUZZY_QUALITY))
+    }
+
+    /// Character positions in the app's name that match the query, for highlighting.
+    /// Only called for the rows being drawn; ranking uses the cheaper `score_field`.
+    pub fn match_positions(&self, app: &AppEntry, query: &SearchQuery) -> Vec<usize> {
+        if query.text.is_empty() {
+            return Vec::new();
+        }
+
+        // Lowercasing changes the length of a few characters; positions would be off then
+        let name_lower = app.name.to_lowercase();
+        if name_lower.chars().count() != app.name.chars().count() {
+            return Vec::new();
+        }
+
+        // Same order as score_field: a prefix match highlights the prefix
+        if name_lower.starts_with(&query.lower) {
+            return (0..query.lower.chars().count()).collect();
+        }
+        self.fuzzy_matcher
+            .fuzzy_indices(&name_lower, &query.lower)
+            .map(|(_, positions)| positions)
+            .unwrap_or_default()
+    }
+
+    /// Combine a field's weight with the quality of its match: the weight ranks
+    /// first, so a name match beats any keyword match, and the quality second
+    fn weighted(quality: i64, weight: f64) -> i64 {
+        ((weight * FIELD_RANK) as i64).saturating_add(quality)
     }
 
     /// Load apps based on the single pane mode
-    fn load_for_mode(mode: SinglePaneMode) -> (Vec<String>, Vec<AppEntry>) {
+    fn load_for_mode(mode: SinglePaneMode, config: &DstlConfig) -> (Vec<String>, Vec<AppEntry>) {
         let (categories, mut apps) = match mode {
-            SinglePaneMode::DesktopApps => Self::load_desktop_apps(),
-            SinglePaneMode::Dmenu => Self::load_from_path("/usr/bin"),
+            SinglePaneMode::DesktopApps => Self::load_desktop_apps(config),
+            SinglePaneMode::Run => Self::load_from_path(config),
+            // Keep stdin order, like dmenu does
+            SinglePaneMode::Dmenu => return Self::load_from_stdin(),
         };
         
         // Sort apps alphabetically for single pane mode
-        apps.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        apps.sort_by_key(|a| a.name.to_lowercase());
         
         (categories, apps)
     }
 
-    /// Load .desktop apps from local and system directories
-    fn load_desktop_apps() -> (Vec<String>, Vec<AppEntry>) {
+    /// Load .desktop apps from the XDG data directories
+    fn load_desktop_apps(config: &DstlConfig) -> (Vec<String>, Vec<AppEntry>) {
         use std::collections::{HashMap, HashSet};
 
         let mut apps = Vec::new();
         let mut category_map: HashMap<String, Vec<String>> = HashMap::new();
         let mut seen_apps: HashSet<String> = HashSet::new();
-        let mut seen_files: HashSet<String> = HashSet::new(); // Track processed .desktop files
 
-        let home = std::env::var("HOME").unwrap_or_else(|_| String::from("/home"));
-        let local_dir = format!("{}/.local/share/applications", home);
+        // Entries from the config come first, so they win over a .desktop file of the same name
+        for custom in &config.entries {
+            let entry = AppEntry::from_custom(custom);
+            if config.exclude.matches(&entry.id, &entry.name, &entry.exec) || !seen_apps.insert(entry.name.clone()) {
+                continue;
+            }
+            category_map.entry(entry.category.clone()).or_default().push(entry.name.clone());
+            apps.push(entry);
+        }
 
-        let paths = vec![local_dir, "/usr/share/applications".to_string()];
+        // Locale fallbacks for Name[xx]= style keys, best match first
+        let locales = desktop::locale_candidates();
 
         // Get current desktop environment once
         let current_desktops: Vec<String> = std::env::var("XDG_CURRENT_DESKTOP")
@@ -341,231 +875,402 @@ impl App {
             .map(|s| s.trim().to_lowercase())
             .collect();
 
-        for dir in paths {
-            if let Ok(entries) = fs::read_dir(&dir) {
-                for entry in entries.flatten() {
-                    let path = entry.path();
-                    if path.extension().and_then(|s| s.to_str()) != Some("desktop") {
+        for (id, path) in desktop::desktop_files() {
+            if let Ok(content) = fs::read_to_string(&path) {
+                let mut name = desktop::Localized::default();
+                let mut generic_name = desktop::Localized::default();
+                let mut comment = desktop::Localized::default();
+                let mut keywords = desktop::Localized::default();
+                let mut exec = None;
+                let mut icon = None;
+                let mut try_exec = None;
+                let mut working_dir = None;
+                let mut categories = None;
+                let mut no_display = false;
+                let mut terminal = false;
+                let mut only_show_in: Option<Vec<String>> = None;
+                let mut not_show_in: Option<Vec<String>> = None;
+                let mut action_ids: Vec<String> = Vec::new();
+                let mut action_groups: Vec<(String, desktop::Localized, Option<String>)> = Vec::new();
+                let mut in_desktop_entry = false;
+                let mut in_action = false;
+
+                for line in content.lines() {
+                    let line = line.trim();
+                
+                    // Track sections
+                    if line.starts_with('[') {
+                        in_desktop_entry = line == "[Desktop Entry]";
+
+                        // [Desktop Action new-window] groups describe extra launch actions
+                        let action_id = line
+                            .strip_prefix("[Desktop Action ")
+                            .and_then(|rest| rest.strip_suffix(']'));
+                        in_action = action_id.is_some();
+                        if let Some(id) = action_id {
+                            action_groups.push((id.to_string(), desktop::Localized::default(), None));
+                        }
                         continue;
                     }
 
-                    // Get the base filename to check for duplicates across directories
-                    let filename = path.file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("")
-                        .to_string();
-                    
-                    // Skip if we've already processed this .desktop file from another directory
-                    if seen_files.contains(&filename) {
+                    if in_action
+                        && let Some((key, value)) = line.split_once('=')
+                        && let Some((_, action_name, action_exec)) = action_groups.last_mut()
+                    {
+                        match desktop::split_locale_key(key.trim()) {
+                            ("Name", locale) => action_name.set(locale, value.trim(), &locales),
+                            ("Exec", None) => *action_exec = Some(value.trim().to_string()),
+                            _ => {}
+                        }
+                        continue;
+                    }
+                
+                    // Only parse inside [Desktop Entry] section
+                    if !in_desktop_entry {
                         continue;
                     }
-                    seen_files.insert(filename.clone());
-
-                    if let Ok(content) = fs::read_to_string(&path) {
-                        let mut name = None;
-                        let mut generic_name = None;
-                        let mut exec = None;
-                        let mut categories = None;
-                        let mut no_display = false;
-                        let mut terminal = false;
-                        let mut only_show_in: Option<Vec<String>> = None;
-                        let mut not_show_in: Option<Vec<String>> = None;
-                        let mut in_desktop_entry = false;
-
-                        for line in content.lines() {
-                            let line = line.trim();
-                            
-                            // Track sections
-                            if line.starts_with('[') {
-                                in_desktop_entry = line == "[Desktop Entry]";
-                                continue;
-                            }
-                            
-                            // Only parse inside [Desktop Entry] section
-                            if !in_desktop_entry {
-                                continue;
-                            }
-                            
-                            // Parse key=value pairs
-                            if let Some((key, value)) = line.split_once('=') {
-                                // Skip localized entries like Name[af]=, Comment[de]=, etc.
-                                if key.contains('[') {
-                                    continue;
-                                }
-                                
-                                let key = key.trim();
-                                let value = value.trim();
-                                
-                                match key {
-                                    "Name" => name = Some(value.to_string()),
-                                    "GenericName" => generic_name = Some(value.to_string()),
-                                    "Exec" => exec = Some(value.to_string()),
-                                    "Categories" => categories = Some(value.to_string()),
-                                    "NoDisplay" => no_display = value == "true",
-                                    "Hidden" => no_display = no_display || value == "true",
-                                    "Terminal" => terminal = value == "true",
-                                    "OnlyShowIn" => {
-                                        only_show_in = Some(
-                                            value.split(';')
-                                                .map(|s| s.trim())
-                                                .filter(|s| !s.is_empty())
-                                                .map(|s| s.to_string())
-                                                .collect()
-                                        );
-                                    }
-                                    "NotShowIn" => {
-                                        not_show_in = Some(
-                                            value.split(';')
-                                                .map(|s| s.trim())
-                                                .filter(|s| !s.is_empty())
-                                                .map(|s| s.to_string())
-                                                .collect()
-                                        );
-                                    }
-                                    _ => {}
-                                }
-                            }
+                
+                    // Parse key=value pairs
+                    if let Some((key, value)) = line.split_once('=') {
+                        // Localized entries look like Name[de]=, Comment[pt_BR]=, etc.
+                        let (key, locale) = desktop::split_locale_key(key.trim());
+                        let value = value.trim();
+                    
+                        match key {
+                            "Name" => name.set(locale, value, &locales),
+                            "GenericName" => generic_name.set(locale, value, &locales),
+                            "Comment" => comment.set(locale, value, &locales),
+                            "Keywords" => keywords.set(locale, value, &locales),
+                            // Nothing else we read is translatable
+                            _ if locale.is_some() => {}
+                            "Exec" => exec = Some(value.to_string()),
+                            "Icon" => icon = Some(value.to_string()),
+                            "TryExec" => try_exec = Some(desktop::unescape_value(value)),
+                            "Path" => working_dir = Some(PathBuf::from(desktop::unescape_value(value))),
+                            "Categories" => categories = Some(value.to_string()),
+                            "Actions" => action_ids = desktop::split_list(value),
+                            "NoDisplay" => no_display = value == "true",
+                            "Hidden" => no_display = no_display || value == "true",
+                            "Terminal" => terminal = value == "true",
+                            "OnlyShowIn" => only_show_in = Some(desktop::split_list(value)),
+                            "NotShowIn" => not_show_in = Some(desktop::split_list(value)),
+                            _ => {}
                         }
+                    }
+                }
 
-                        // Skip apps marked as NoDisplay or Hidden
-                        if no_display {
-                            continue;
-                        }
-                        
-                        // Use Name, or fallback to GenericName
-                        let name = name.or(generic_name);
-
-                        // Check OnlyShowIn - skip if specified and current desktop not in list
-                        if let Some(desktops) = &only_show_in {
-                            let allowed = desktops.iter()
-                                .any(|d| current_desktops.contains(&d.to_lowercase()));
-                            
-                            if !allowed {
-                                continue;
-                            }
-                        }
+                // Skip apps marked as NoDisplay or Hidden
+                if no_display {
+                    continue;
+                }
+            
+                // Use Name, or fallback to GenericName
+                let generic_name = generic_name.value();
+                let name = name.value().or(generic_name.clone());
 
-                        // Check NotShowIn - skip if current desktop is in list
-                        if let Some(desktops) = &not_show_in {
-                            let blocked = desktops.iter()
-                                .any(|d| current_desktops.contains(&d.to_lowercase()));
-                            if blocked {
-                                continue;
-                            }
-                        }
+                // Check OnlyShowIn - skip if specified and current desktop not in list
+                if let Some(desktops) = &only_show_in {
+                    let allowed = desktops.iter()
+                        .any(|d| current_desktops.contains(&d.to_lowercase()));
+                
+                    if !allowed {
+                        continue;
+                    }
+                }
 
-                        if let (Some(name), Some(exec)) = (name, exec) {
-                            // Skip if we've already seen this app name
-                            if seen_apps.contains(&name) {
-                                continue;
-                            }
-                            seen_apps.insert(name.clone());
-                            
-                            // Determine grouped category             
-                            let cat_group = if let Some(cats) = categories {
-                                Self::group_category(&cats, &name)
-                            } else {
-                                Self::group_category("", &name)
-                            };
-
-                            // Clean up Exec field codes (%f, %F, %u, %U, etc.)
-                            let exec_clean = Self::clean_exec(&exec);
-
-                            apps.push(AppEntry {
-                                name: name.clone(),
-                                category: cat_group.clone(),
-                                exec: exec_clean,
-                                terminal,
-                            });
-
-                            category_map
-                                .entry(cat_group)
-                                .or_default()
-                                .push(name);
-                        }
+                // Check NotShowIn - skip if current desktop is in list
+                if let Some(desktops) = &not_show_in {
+                    let blocked = desktops.iter()
+                        .any(|d| current_desktops.contains(&d.to_lowercase()));
+                    if blocked {
+                        continue;
+                    }
+                }
+
+                // TryExec names a binary that must exist, otherwise the entry is broken
+                if let Some(ref program) = try_exec
+                    && launch::find_program(program).is_none()
+                {
+                    continue;
+                }
+
+                if let (Some(name), Some(exec)) = (name, exec) {
+                    let exec_ctx = desktop::ExecContext {
+                        name: &name,
+                        icon: icon.as_deref(),
+                        desktop_file: &path,
+                    };
+
+                    // Entries whose Exec doesn't parse can't be launched
+                    let Some(argv) = desktop::parse_exec(&exec, &exec_ctx) else {
+                        continue;
+                    };
+
+                    if config.exclude.matches(&id, &name, &exec) {
+                        continue;
+                    }
+
+                    // Skip if we've already seen this app name
+                    if seen_apps.contains(&name) {
+                        continue;
+                    }
+                    seen_apps.insert(name.clone());
+                
+                    // Determine grouped categories, keeping only the best one if asked to
+                    let categories = categories.map(|c| desktop::split_list(&c)).unwrap_or_default();
+                    let mut groups = config.categories.groups(&categories, &id, &name);
+                    if !config.multi_category {
+                        groups.truncate(1);
+                    }
+
+                    // Only actions listed in Actions= count, in that order
+                    let actions = action_ids
+                        .iter()
+                        .filter_map(|id| {
+                            let (_, action_name, action_exec) = action_groups.iter().find(|(g, _, _)| g == id)?;
+                            let argv = desktop::parse_exec(action_exec.as_ref()?, &exec_ctx)?;
+                            let exec = desktop::shell_join(&argv);
+                            let (env, argv) = desktop::split_env(argv);
+                            Some(DesktopAction {
+                                name: action_name.clone().value()?,
+                                exec,
+                                argv,
+                                env,
+                            })
+                        })
+                        .collect();
+
+                    let exec = desktop::shell_join(&argv);
+                    let (env, argv) = desktop::split_env(argv);
+
+                    apps.push(AppEntry {
+                        id,
+                        name: name.clone(),
+                        category: groups[0].clone(),
+                        groups: groups.clone(),
+                        categories,
+                        desktop_file: Some(path.clone()),
+                        exec,
+                        argv,
+                        env,
+                        working_dir,
+                        terminal,
+                        terminal_explicit: false,
+                        // Don't repeat the name when it came from GenericName
+                        generic_name: generic_name.filter(|g| *g != name),
+                        comment: comment.value(),
+                        keywords: keywords
+                            .value()
+                            .map(|k| desktop::split_list(&k))
+                            .unwrap_or_default(),
+                        actions,
+                        glyph: None,
+                    });
+
+                    for group in &groups {
+                        category_map
+                            .entry(group.clone())
+                            .or_default()
+                            .push(name.clone());
                     }
                 }
             }
         }
 
-        // Build the list of grouped categories with Recent first
+        // Build the list of grouped categories with Recent first; Favorites is
+        // added next to it once the history shows something is pinned
         let mut categories = vec!["Recent".to_string()];
-        
-        let category_order = vec![
-            "Utilities", "Development", "Network", "Audio/Video", "Graphics",
-            "System", "Office", "Games", "Education", "Settings"
-        ];
-        categories.extend(
-            category_order
-                .into_iter()
-                .filter(|c| category_map.contains_key(*c))
-                .map(|s| s.to_string())
-        );
+
+        // Configured categories in their display order, then any others in use
+        // (the fallback, categories only custom entries use) in first-seen order
+        let configured = config.categories.rules.iter().map(|r| &r.name);
+        let others = apps.iter().flat_map(|a| &a.groups);
+        for category in configured.chain(others) {
+            if category_map.contains_key(category) && !categories.contains(category) {
+                categories.push(category.clone());
+            }
+        }
 
         (categories, apps)
     }
 
-    /// Clean desktop entry Exec field by removing field codes
-    fn clean_exec(exec: &str) -> String {
-        // Remove field codes like %f, %F, %u, %U, %d, %D, %n, %N, %i, %c, %k, %v, %m
-        let mut result = exec.to_string();
-        let field_codes = ["%f", "%F", "%u", "%U", "%d", "%D", "%n", "%N", "%i", "%c", "%k", "%v", "%m"];
-        for code in &field_codes {
-            result = result.replace(code, "");
-        }
-        result.trim().to_string()
+    /// Read newline-separated candidates from stdin (dmenu style)
+    fn load_from_stdin() -> (Vec<String>, Vec<AppEntry>) {
+        let apps = std::io::stdin()
+            .lines()
+            .map_while(Result::ok)
+            .filter(|line| !line.is_empty())
+            .map(AppEntry::from_line)
+            .collect();
+
+        (vec!["Dmenu".to_string()], apps)
     }
 
-    /// Map raw .desktop categories to simplified groupings 
-    fn group_category(raw: &str, app_name: &str) -> String {
-        let raw = raw.to_lowercase();
+    /// Load executables from every $PATH directory (run mode)
+    fn load_from_path(config: &DstlConfig) -> (Vec<String>, Vec<AppEntry>) {
+        use std::collections::HashSet;
 
-        // Special case for Claw
-        if app_name.to_lowercase() == "claw" {
-            return "Utilities".to_string();
-        }
+        let mut apps = Vec::new();
+        let mut seen_names: HashSet<String> = HashSet::new();
 
-        if app_name.to_lowercase() == "rofi" {
-            return "Utilities".to_string();
-        }
+        let path_var = std::env::var_os("PATH")
+            .unwrap_or_else(|| "/usr/local/bin:/usr/bin:/bin".into());
 
-        // Prioritize "game" before "network"
-        if raw.contains("game") { "Games".to_string() }
-        else if raw.contains("utility") { "Utilities".to_string() }
-        else if raw.contains("development") { "Development".to_string() }
-        else if raw.contains("network") { "Network".to_string() }
-        else if raw.contains("audio") || raw.contains("video") { "Audio/Video".to_string() }
-        else if raw.contains("graphics") || raw.contains("2dgraphics") || raw.contains("3dgraphics") {
-            "Graphics".to_string()
-        }
-        else if raw.contains("system") { "System".to_string() }
-        else if raw.contains("office") { "Office".to_string() }
-        else if raw.contains("education") { "Education".to_string() }
-        else if raw.contains("settings") { "Settings".to_string() }
-        else { "Utilities".to_string() }
-    }
+        // Earlier PATH entries win, just like the shell's lookup
+        for dir in std::env::split_paths(&path_var) {
+            // Empty entries mean the cwd, which we never want to list
+            if dir.as_os_str().is_empty() {
+                continue;
+            }
 
-    /// Load executables from a directory (dmenu style)
-    fn load_from_path<P: AsRef<Path>>(path: P) -> (Vec<String>, Vec<AppEntry>) {
-        let mut apps = Vec::new();
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
 
-        if let Ok(entries) = fs::read_dir(path) {
             for entry in entries.flatten() {
                 let path = entry.path();
-                if path.is_file() {
-                    if let Some(name) = path.file_name().and_then(|s| s.to_str()) {
-                        apps.push(AppEntry {
-                            name: name.to_string(),
-                            category: "CLI".to_string(),
-                            exec: name.to_string(),
-                            terminal: true,
-                        });
-                    }
+                let Some(name) = path.file_name().and_then(|s| s.to_str()) else {
+                    continue;
+                };
+
+                if seen_names.contains(name) || !launch::is_executable(&path) {
+                    continue;
                 }
+                seen_names.insert(name.to_string());
+
+                let path_str = path.to_string_lossy();
+                if config.exclude.matches(&path_str, name, &path_str) {
+                    continue;
+                }
+
+                apps.push(AppEntry {
+                    id: path.to_string_lossy().into_owned(),
+                    name: name.to_string(),
+                    category: "CLI".to_string(),
+                    groups: vec!["CLI".to_string()],
+                    categories: Vec::new(),
+                    desktop_file: None,
+                    // Launch the binary PATH lookup resolved, not whatever shadows it later
+                    exec: path.to_string_lossy().into_owned(),
+                    argv: vec![path.to_string_lossy().into_owned()],
+                    env: Vec::new(),
+                    working_dir: None,
+                    terminal: true,
+                    terminal_explicit: false,
+                    generic_name: None,
+                    comment: None,
+                    keywords: Vec::new(),
+                    actions: Vec::new(),
+                    glyph: None,
+                });
             }
         }
 
-        // Dmenu-style uses CLI category for consistency
+        // Run mode uses CLI category for consistency
         (vec!["CLI".to_string()], apps)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+
+    fn app_with_queries(queries: &[&str]) -> App {
+        let mut app = App::with_apps(
+            SinglePaneMode::DesktopApps,
+            Mode::SinglePane,
+            Vec::new(),
+            Vec::new(),
+            &config::default_config(),
+        );
+        // Recorded oldest first, so the last one is the newest
+        for query in queries {
+            app.history.record_query(query, "app.desktop");
+        }
+        app
+    }
+
+    #[test]
+    fn recent_skips_ids_that_are_not_listed() {
+        let mut config = config::default_config();
+        config.max_recent_apps = 2;
+        let apps = ["a.desktop", "b.desktop", "c.desktop"]
+            .into_iter()
+            .map(|id| AppEntry::from_line(id.to_string()))
+            .collect();
+        let mut app = App::with_apps(SinglePaneMode::DesktopApps, Mode::DualPane, Vec::new(), apps, &config);
+
+        // A run-mode path and an uninstalled app outrank everything that's listed
+        for _ in 0..3 {
+            app.history.record("/usr/bin/htop");
+            app.history.record("gone.desktop");
+        }
+        app.history.record("a.desktop");
+        app.history.record("a.desktop");
+        app.history.record("b.desktop");
+        app.refresh_recent();
+        assert_eq!(app.recent_apps, ["a.desktop", "b.desktop"]);
+
+        // Hiding an app frees its slot too
+        app.history.record("c.desktop");
+        app.history.toggle_hidden("a.desktop");
+        app.refresh_recent();
+        assert_eq!(app.recent_apps.len(), 2);
+        assert!(!app.recent_apps.contains(&"a.desktop".to_string()));
+    }
+
+    #[test]
+    fn recall_steps_back_newest_first_and_stops_at_the_oldest() {
+        let mut app = app_with_queries(&["old", "mid", "new"]);
+
+        app.recall_query(true);
+        assert_eq!(app.query.text(), "new");
+        app.recall_query(true);
+        assert_eq!(app.query.text(), "mid");
+        app.recall_query(true);
+        app.recall_query(true);
+        assert_eq!(app.query.text(), "old");
+    }
+
+    #[test]
+    fn recall_past_the_newest_restores_the_draft() {
+        let mut app = app_with_queries(&["old", "new"]);
+        app.query.set_text("dra");
+
+        app.recall_query(true);
+        app.recall_query(true);
+        app.recall_query(false);
+        assert_eq!(app.query.text(), "new");
+        app.recall_query(false);
+        assert_eq!(app.query.text(), "dra");
+        // Already at the draft: nothing newer to go to
+        app.recall_query(false);
+        assert_eq!(app.query.text(), "dra");
+    }
+
+    #[test]
+    fn editing_a_recalled_search_starts_over_from_the_newest() {
+        let mut app = app_with_queries(&["old", "new"]);
+
+        app.recall_query(true);
+        app.recall_query(true);
+        assert_eq!(app.query.text(), "old");
+        app.query.insert('s');
+
+        app.recall_query(true);
+        assert_eq!(app.query.text(), "new");
+        app.recall_query(false);
+        assert_eq!(app.query.text(), "olds");
+    }
+
+    #[test]
+    fn recall_without_history_leaves_the_query() {
+        let mut app = app_with_queries(&[]);
+        app.query.set_text("typed");
+
+        app.recall_query(true);
+        assert_eq!(app.query.text(), "typed");
+        assert_eq!(app.query_recall, None);
+    }
+}
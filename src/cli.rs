@@ -0,0 +1,217 @@
+use std::path::PathBuf;
+use std::process;
+use crate::config::{self, DstlConfig, SearchPosition, StartMode};
+
+const HELP: &str = "\
+dstl - Dustin's Simple TUI Launcher
+
+Usage: dstl [OPTIONS]
+
+Options:
+  -c, --config <path>            Use this config file instead of ~/.config/dstl/dstl.rune
+  -m, --mode <single|dual|run>   Start in single-pane, dual-pane or run (PATH) mode
+      --search-position <top|bottom>
+                                 Where the search bar is drawn
+  -t, --terminal <cmd>           Terminal emulator for terminal apps
+  -p, --prompt <text>            Title of the search bar
+      --timeout <secs>           Quit after this many idle seconds (0 disables)
+      --theme <file>             Load dstl.theme colors from another rune file
+      --print-only               Print the selected command instead of launching it
+      --dmenu                    Read candidates from stdin and print the selection
+  -h, --help                     Print this help
+  -V, --version                  Print the version
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CliMode {
+    Single,
+    Dual,
+    Run,
+}
+
+/// Command-line options; every `Some` value overrides the rune config
+#[derive(Debug, Clone, Default)]
+pub struct Cli {
+    pub config: Option<PathBuf>,
+    pub mode: Option<CliMode>,
+    pub search_position: Option<SearchPosition>,
+    pub terminal: Option<String>,
+    pub prompt: Option<String>,
+    pub timeout: Option<u64>,
+    pub theme: Option<PathBuf>,
+    pub print_only: bool,
+    pub dmenu: bool,
+}
+
+impl Cli {
+    /// Parse the process arguments, exiting on --help, --version or bad input
+    pub fn parse() -> Self {
+        match Self::parse_from(std::env::args().skip(1)) {
+            Ok(cli) => cli,
+            Err(e) => {
+                eprintln!("❌ {}\nTry 'dstl --help' for more information.", e);
+                process::exit(2);
+            }
+        }
+    }
+
+    fn parse_from<I: Iterator<Item = String>>(mut args: I) -> Result<Self, String> {
+        let mut cli = Cli::default();
+
+        while let Some(arg) = args.next() {
+            // Accept both `--flag value` and `--flag=value`
+            let (flag, inline) = match arg.split_once('=') {
+                Some((f, v)) if f.starts_with("--") => (f.to_string(), Some(v.to_string())),
+                _ => (arg, None),
+            };
+
+            let mut value = |name: &str| -> Result<String, String> {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("Missing value for '{}'", name))
+            };
+
+            match flag.as_str() {
+                "-h" | "--help" => {
+                    print!("{}", HELP);
+                    process::exit(0);
+                }
+                "-V" | "--version" => {
+                    println!("dstl {}", env!("CARGO_PKG_VERSION"));
+                    process::exit(0);
+                }
+                "-c" | "--config" => cli.config = Some(PathBuf::from(value(&flag)?)),
+                "-m" | "--mode" => {
+                    cli.mode = Some(match value(&flag)?.to_lowercase().as_str() {
+                        "single" => CliMode::Single,
+                        "dual" => CliMode::Dual,
+                        "run" => CliMode::Run,
+                        other => return Err(format!("Invalid mode '{}' (expected single, dual or run)", other)),
+                    });
+                }
+                "--search-position" => {
+                    cli.search_position = Some(match value(&flag)?.to_lowercase().as_str() {
+                        "top" => SearchPosition::Top,
+                        "bottom" => SearchPosition::Bottom,
+                        other => return Err(format!("Invalid search position '{}' (expected top or bottom)", other)),
+                    });
+                }
+                "-t" | "--terminal" => cli.terminal = Some(value(&flag)?),
+                "-p" | "--prompt" => cli.prompt = Some(value(&flag)?),
+                "--timeout" => {
+                    let raw = value(&flag)?;
+                    cli.timeout = Some(
+                        raw.parse()
+                            .map_err(|_| format!("Invalid timeout '{}' (expected seconds)", raw))?,
+                    );
+                }
+                "--theme" => cli.theme = Some(PathBuf::from(value(&flag)?)),
+                "--print-only" => cli.print_only = true,
+                "--dmenu" => cli.dmenu = true,
+                other => return Err(format!("Unknown argument '{}'", other)),
+            }
+        }
+
+        Ok(cli)
+    }
+
+    /// Layer the command-line values on top of the loaded config
+    pub fn apply(&self, cfg: &mut DstlConfig) {
+        match self.mode {
+            Some(CliMode::Single) => {
+                cfg.dmenu = false;
+                cfg.start_mode = StartMode::Single;
+            }
+            Some(CliMode::Dual) => {
+                cfg.dmenu = false;
+                cfg.start_mode = StartMode::Dual;
+            }
+            Some(CliMode::Run) => {
+                cfg.dmenu = true;
+                cfg.start_mode = StartMode::Single;
+            }
+            None => {}
+        }
+
+        if let Some(ref pos) = self.search_position {
+            cfg.search_position = pos.clone();
+        }
+        if let Some(ref terminal) = self.terminal {
+            cfg.terminal = terminal.clone();
+        }
+        if let Some(ref prompt) = self.prompt {
+            cfg.prompt = prompt.clone();
+        }
+        if let Some(timeout) = self.timeout {
+            cfg.timeout = timeout;
+        }
+
+        if let Some(ref theme) = self.theme {
+            config::apply_theme_file(theme, &mut cfg.colors).unwrap_or_else(|e| {
+                eprintln!("❌ Theme error:\n{}", e);
+                process::exit(1);
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, String> {
+        Cli::parse_from(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn flags_take_separate_or_inline_values() {
+        let cli = parse(&[
+            "-c", "/tmp/dstl.rune",
+            "--mode=Dual",
+            "--search-position", "bottom",
+            "-t", "kitty",
+            "--prompt=Run",
+            "--timeout", "5",
+            "--print-only",
+        ])
+        .unwrap();
+
+        assert_eq!(cli.config, Some(PathBuf::from("/tmp/dstl.rune")));
+        assert_eq!(cli.mode, Some(CliMode::Dual));
+        assert_eq!(cli.search_position, Some(SearchPosition::Bottom));
+        assert_eq!(cli.terminal.as_deref(), Some("kitty"));
+        assert_eq!(cli.prompt.as_deref(), Some("Run"));
+        assert_eq!(cli.timeout, Some(5));
+        assert!(cli.print_only);
+        assert!(!cli.dmenu);
+        assert_eq!(cli.theme, None);
+    }
+
+    #[test]
+    fn no_arguments_override_nothing() {
+        let cli = parse(&[]).unwrap();
+        assert_eq!(cli.mode, None);
+        assert_eq!(cli.timeout, None);
+        assert!(!cli.print_only);
+    }
+
+    #[test]
+    fn unknown_flags_are_rejected() {
+        assert_eq!(parse(&["--frobnicate"]).unwrap_err(), "Unknown argument '--frobnicate'");
+        assert_eq!(parse(&["run"]).unwrap_err(), "Unknown argument 'run'");
+    }
+
+    #[test]
+    fn missing_values_are_rejected() {
+        assert_eq!(parse(&["--mode"]).unwrap_err(), "Missing value for '--mode'");
+        assert_eq!(parse(&["--dmenu", "-t"]).unwrap_err(), "Missing value for '-t'");
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        assert!(parse(&["--mode", "triple"]).unwrap_err().starts_with("Invalid mode 'triple'"));
+        assert!(parse(&["--search-position=left"]).unwrap_err().starts_with("Invalid search position 'left'"));
+        assert!(parse(&["--timeout", "soon"]).unwrap_err().starts_with("Invalid timeout 'soon'"));
+    }
+}
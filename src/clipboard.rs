@@ -0,0 +1,60 @@
+use std::env;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Clipboard readers, tried in order when their display server is running
+const READERS: [(&str, &[&str]); 2] = [
+    ("WAYLAND_DISPLAY", &["wl-paste", "--no-newline"]),
+    ("DISPLAY", &["xclip", "-selection", "clipboard", "-o"]),
+];
+
+/// How long a reader may take; xclip waits forever on a selection owner that doesn't answer
+const TIMEOUT: Duration = Duration::from_millis(500);
+/// How often a running reader is checked on
+const POLL: Duration = Duration::from_millis(10);
+
+/// Read the clipboard through wl-paste or xclip; `None` when neither is available
+pub fn read() -> Option<String> {
+    READERS
+        .iter()
+        .filter(|(display, _)| env::var_os(display).is_some())
+        .find_map(|(_, argv)| run(argv))
+}
+
+/// Run a reader and collect its output, killing it once TIMEOUT passes
+fn run(argv: &[&str]) -> Option<String> {
+    let mut child = Command::new(argv[0])
+        .args(&argv[1..])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    // Drain stdout on a thread so a large clipboard can't fill the pipe and stall the reader
+    let mut stdout = child.stdout.take()?;
+    let output = thread::spawn(move || {
+        let mut bytes = Vec::new();
+        stdout.read_to_end(&mut bytes).map(|_| bytes)
+    });
+
+    let deadline = Instant::now() + TIMEOUT;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(POLL),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    };
+
+    let bytes = output.join().ok()?.ok()?;
+    status
+        .success()
+        .then(|| String::from_utf8_lossy(&bytes).into_owned())
+}
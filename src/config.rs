@@ -1,10 +1,12 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
-use eyre::Result;
-use ratatui::style::Color;
+use eyre::{eyre, Result};
+use ratatui::style::{Color, Modifier, Style};
 use ratatui::widgets::BorderType;
+use regex::Regex;
 use rune_cfg::{RuneConfig, Value, RuneError};
 use serde::{Deserialize, Serialize};
+use crate::keymap::Keymap;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum SearchPosition {
@@ -12,6 +14,12 @@ pub enum SearchPosition {
     Bottom,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DetailsPosition {
+    Right,
+    Bottom,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum StartMode {
     Single,
@@ -33,10 +41,165 @@ pub struct LauncherTheme {
     pub border_style: String,
     pub highlight_type: String,
     pub cursor_color: String,
+    pub details_label: String,
+    pub match_color: String,
+    /// "bold", "underline", "bold underline" or "none"
+    pub match_style: String,
     pub cursor_shape: CursorShape,
     pub cursor_blink_interval: u64,
 }
 
+/// Largest accepted `dstl.search_weights` value
+const MAX_SEARCH_WEIGHT: f64 = 10.0;
+
+/// How much each desktop entry field counts when scoring a search match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchWeights {
+    pub name: f64,
+    pub generic_name: f64,
+    pub keywords: f64,
+    pub comment: f64,
+    pub exec: f64,
+}
+
+/// Entries left out of every list through `dstl.exclude`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExcludeRules {
+    /// Plain strings: a display name (case-insensitive) or desktop-file ID
+    pub entries: Vec<String>,
+    /// `r"..."` patterns matched against the Exec line
+    #[serde(skip)]
+    pub exec: Vec<Regex>,
+}
+
+impl ExcludeRules {
+    pub fn matches(&self, id: &str, name: &str, exec: &str) -> bool {
+        // Full Unicode case folding, so "éditeur" still excludes "Éditeur"
+        let name = name.to_lowercase();
+        self.entries.iter().any(|e| e == id || e.to_lowercase() == name)
+            || self.exec.iter().any(|re| re.is_match(exec))
+    }
+}
+
+/// One `dstl.categories` block: a dual-pane category and the apps that go in it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryRule {
+    pub name: String,
+    /// freedesktop `Categories=` values that map here (case-insensitive)
+    pub matches: Vec<String>,
+    /// Substrings of `Categories=` values that map here (case-insensitive),
+    /// e.g. `game` for `Game`, `ActionGame` and `BoardGame`
+    pub contains: Vec<String>,
+    /// App names or desktop-file IDs always put here, whatever they declare
+    pub apps: Vec<String>,
+    /// Rules with a higher priority are tried first; ties go in declaration order
+    pub priority: i64,
+    /// Glyph shown next to the category, instead of the built-in one
+    pub icon: Option<String>,
+}
+
+impl CategoryRule {
+    /// Whether a single `Categories=` value maps into this rule
+    fn claims(&self, declared: &str) -> bool {
+        let lower = declared.to_lowercase();
+        self.matches.iter().any(|m| m.to_lowercase() == lower)
+            || self.contains.iter().any(|c| lower.contains(&c.to_lowercase()))
+    }
+}
+
+/// How desktop apps are grouped into categories, in display order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryRules {
+    pub rules: Vec<CategoryRule>,
+    /// Category for apps no rule claims
+    pub fallback: String,
+}
+
+impl CategoryRules {
+    /// Every category an app belongs in, best match first; never empty
+    pub fn groups(&self, declared: &[String], id: &str, name: &str) -> Vec<String> {
+        if let Some(rule) = self.rules.iter().find(|r| {
+            r.apps.iter().any(|a| a == id || a.eq_ignore_ascii_case(name))
+        }) {
+            return vec![rule.name.clone()];
+        }
+
+        let mut by_priority: Vec<&CategoryRule> = self.rules.iter().collect();
+        by_priority.sort_by_key(|r| std::cmp::Reverse(r.priority));
+
+        let groups: Vec<String> = by_priority
+            .into_iter()
+            .filter(|r| declared.iter().any(|d| r.claims(d)))
+            .map(|r| r.name.clone())
+            .collect();
+
+        if groups.is_empty() {
+            vec![self.fallback.clone()]
+        } else {
+            groups
+        }
+    }
+
+    /// The configured glyph for a category, if it has one
+    pub fn icon(&self, category: &str) -> Option<&str> {
+        self.rules.iter().find(|r| r.name == category)?.icon.as_deref()
+    }
+}
+
+impl Default for CategoryRules {
+    fn default() -> Self {
+        // Substring matches, like the original mapping: "game" also takes
+        // ActionGame, "settings" takes X-GNOME-Settings-Panel
+        let rule = |name: &str, contains: &[&str], apps: &[&str]| CategoryRule {
+            name: name.to_string(),
+            matches: Vec::new(),
+            contains: contains.iter().map(|s| s.to_string()).collect(),
+            apps: apps.iter().map(|s| s.to_string()).collect(),
+            priority: 0,
+            icon: None,
+        };
+
+        let mut rules = vec![
+            rule("Utilities", &["utility"], &["claw", "rofi"]),
+            rule("Development", &["development"], &[]),
+            rule("Network", &["network"], &[]),
+            rule("Audio/Video", &["audio", "video"], &[]),
+            rule("Graphics", &["graphics"], &[]),
+            rule("System", &["system"], &[]),
+            rule("Office", &["office"], &[]),
+            rule("Games", &["game"], &[]),
+            rule("Education", &["education"], &[]),
+            rule("Settings", &["settings"], &[]),
+        ];
+
+        // Games usually also list Network, and should still land in Games
+        if let Some(games) = rules.iter_mut().find(|r| r.name == "Games") {
+            games.priority = 1;
+        }
+
+        Self {
+            rules,
+            fallback: "Utilities".to_string(),
+        }
+    }
+}
+
+/// A launcher entry declared under `dstl.entries` instead of in a .desktop file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomEntry {
+    /// Its key under `dstl.entries`, which keeps its history stable across renames
+    pub key: String,
+    pub name: String,
+    /// Shell command line
+    pub exec: String,
+    pub category: String,
+    pub terminal: bool,
+    pub keywords: Vec<String>,
+    pub comment: Option<String>,
+    /// Glyph shown in front of the name
+    pub icon: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DstlConfig {
     pub dmenu: bool,
@@ -45,9 +208,26 @@ pub struct DstlConfig {
     pub focus_search_on_switch: bool,
     pub colors: LauncherTheme,
     pub terminal: String,
+    pub prompt: String,
     pub timeout: u64,
     pub max_recent_apps: usize,
     pub recent_first: bool,
+    pub frecency_weight: f64,
+    pub favorites_first: bool,
+    pub pinned_first: bool,
+    pub search_weights: SearchWeights,
+    pub exclude: ExcludeRules,
+    pub entries: Vec<CustomEntry>,
+    pub categories: CategoryRules,
+    pub multi_category: bool,
+    pub show_details: bool,
+    pub details_position: DetailsPosition,
+    /// Rows for a bottom detail pane, percent of the width for a right one
+    pub details_size: u16,
+    pub mouse: bool,
+    pub vim_mode: bool,
+    #[serde(skip)]
+    pub keys: Keymap,
 }
 
 impl LauncherTheme {
@@ -56,9 +236,7 @@ impl LauncherTheme {
         let color = color.trim();
         
         // Handle hex colors (#RGB, #RRGGBB, #RRGGBBAA)
-        if color.starts_with('#') {
-            let hex = &color[1..];
-            
+        if let Some(hex) = color.strip_prefix('#') {
             match hex.len() {
                 // #RGB format
                 3 => {
@@ -99,6 +277,19 @@ impl LauncherTheme {
         Color::Reset
     }
 
+    /// Style for the matched characters of a search result
+    pub fn match_style(&self) -> Style {
+        let style = self.match_style.to_lowercase();
+        let mut modifier = Modifier::empty();
+        if style.contains("bold") {
+            modifier |= Modifier::BOLD;
+        }
+        if style.contains("underline") {
+            modifier |= Modifier::UNDERLINED;
+        }
+        Style::default().fg(Self::parse_color(&self.match_color)).add_modifier(modifier)
+    }
+
     pub fn parse_border_type(style: &str) -> BorderType {
         match style.to_lowercase().as_str() {
             "plain" => BorderType::Plain,
@@ -139,9 +330,13 @@ fn extract_dstl_config(config: RuneConfig) -> Result<DstlConfig> {
     // --- Fetch values with validation ---
     let dmenu = get_config_or(&config, "dstl.dmenu", false);
     let terminal = get_config_or(&config, "dstl.terminal", "foot".to_string());
+    let prompt = get_config_or(&config, "dstl.prompt", "Search".to_string());
     let timeout = get_config_or(&config, "dstl.timeout", 0u64);
     let max_recent_apps: usize = get_config_or(&config, "dstl.max_recent_apps", 15u64) as usize;
     let recent_first = get_config_or(&config, "dstl.recent_first", false);
+    let frecency_weight = get_config_or(&config, "dstl.frecency_weight", 1.0);
+    let favorites_first = get_config_or(&config, "dstl.favorites_first", true);
+    let pinned_first = get_config_or(&config, "dstl.pinned_first", false);
 
     // Validate search_position
     let search_position_str: String = get_config_or(&config, "dstl.search_position", "top".to_string());
@@ -173,8 +368,42 @@ fn extract_dstl_config(config: RuneConfig) -> Result<DstlConfig> {
     let cursor_blink_interval: u64 = get_config_or(&config, "dstl.theme.cursor_blink_interval", 0u64);
     let border_style: String = get_config_or(&config, "dstl.theme.border_style", "plain".to_string());
     let highlight_type: String = get_config_or(&config, "dstl.theme.highlight_type", "background".to_string());
+    let details_label: String = get_config_or(&config, "dstl.theme.details_label", focus_color.clone());
+    let match_color: String = get_config_or(&config, "dstl.theme.match_color", focus_color.clone());
+    let match_style: String = get_config_or(&config, "dstl.theme.match_style", "bold".to_string());
     let focus_search: bool = get_config_or(&config, "dstl.focus_search_on_switch", true);
 
+    // Name matches rank above everything else by default; out-of-range weights are clamped
+    let weight = |key: &str, default: f64| get_config_or(&config, key, default).clamp(0.0, MAX_SEARCH_WEIGHT);
+    let search_weights = SearchWeights {
+        name: weight("dstl.search_weights.name", 1.0),
+        generic_name: weight("dstl.search_weights.generic_name", 0.7),
+        keywords: weight("dstl.search_weights.keywords", 0.6),
+        comment: weight("dstl.search_weights.comment", 0.3),
+        exec: weight("dstl.search_weights.exec", 0.8),
+    };
+
+    let exclude = load_exclude_rules(&config)?;
+    let categories = load_category_rules(&config);
+    let multi_category = get_config_or(&config, "dstl.multi_category", true);
+    let show_details = get_config_or(&config, "dstl.show_details", false);
+    let mouse = get_config_or(&config, "dstl.mouse", true);
+    let vim_mode = get_config_or(&config, "dstl.vim_mode", false);
+    let keys = load_keymap(&config)?;
+
+    // Validate details_position
+    let details_position_str: String = get_config_or(&config, "dstl.details_position", "bottom".to_string());
+    let details_position = match details_position_str.to_lowercase().as_str() {
+        "right" => DetailsPosition::Right,
+        _ => DetailsPosition::Bottom,
+    };
+    let default_details_size = match details_position {
+        DetailsPosition::Right => 40u64,
+        DetailsPosition::Bottom => 8u64,
+    };
+    let details_size = get_config_or(&config, "dstl.details_size", default_details_size) as u16;
+    let entries = load_custom_entries(&config, &categories.fallback)?;
+
     let colors = LauncherTheme {
         border: border_color,
         focus: focus_color,
@@ -182,6 +411,9 @@ fn extract_dstl_config(config: RuneConfig) -> Result<DstlConfig> {
         border_style,
         highlight_type,
         cursor_color,
+        details_label,
+        match_color,
+        match_style,
         cursor_shape,
         cursor_blink_interval,
     };
@@ -193,12 +425,141 @@ fn extract_dstl_config(config: RuneConfig) -> Result<DstlConfig> {
         focus_search_on_switch: focus_search,
         colors,
         terminal,
+        prompt,
         timeout,
         max_recent_apps,
         recent_first,
+        frecency_weight,
+        favorites_first,
+        pinned_first,
+        search_weights,
+        exclude,
+        entries,
+        categories,
+        multi_category,
+        show_details,
+        details_position,
+        details_size,
+        mouse,
+        vim_mode,
+        keys,
     })
 }
 
+/// Read `dstl.keys`: each action takes a key chord or a list of them, replacing its default keys
+fn load_keymap(config: &RuneConfig) -> Result<Keymap> {
+    let Ok(actions) = config.get_keys("dstl.keys") else {
+        return Ok(Keymap::default());
+    };
+
+    let overrides = actions
+        .into_iter()
+        .map(|action| {
+            let chords = match config.get_value(&format!("dstl.keys.{}", action)) {
+                Ok(Value::String(chord)) => vec![chord],
+                Ok(Value::Array(items)) => items
+                    .into_iter()
+                    .map(|item| match item {
+                        Value::String(chord) => Ok(chord),
+                        other => Err(eyre!("dstl.keys.{} must list keys as strings, found {:?}", action, other)),
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+                other => {
+                    return Err(eyre!("dstl.keys.{} must be a key or a list of keys, found {:?}", action, other));
+                }
+            };
+            Ok((action, chords))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Keymap::default().with_overrides(&overrides)
+}
+
+/// Read `dstl.exclude`: names or desktop-file IDs as strings, Exec patterns as `r"..."`
+fn load_exclude_rules(config: &RuneConfig) -> Result<ExcludeRules> {
+    let mut rules = ExcludeRules::default();
+
+    let items = match config.get_value("dstl.exclude") {
+        Ok(Value::Array(items)) => items,
+        Ok(single) => vec![single],
+        Err(_) => return Ok(rules),
+    };
+
+    for item in items {
+        match item {
+            Value::String(entry) => rules.entries.push(entry),
+            Value::Regex(pattern) => {
+                let re = Regex::new(&pattern)
+                    .map_err(|e| eyre!("Invalid dstl.exclude pattern r\"{}\": {}", pattern, e))?;
+                rules.exec.push(re);
+            }
+            other => {
+                return Err(eyre!(
+                    "dstl.exclude entries must be strings or r\"...\" patterns, found {:?}",
+                    other
+                ));
+            }
+        }
+    }
+
+    Ok(rules)
+}
+
+/// Read the `dstl.entries` blocks; `exec` is the only required field
+fn load_custom_entries(config: &RuneConfig, default_category: &str) -> Result<Vec<CustomEntry>> {
+    let Ok(keys) = config.get_keys("dstl.entries") else {
+        return Ok(Vec::new());
+    };
+
+    keys.into_iter()
+        .map(|key| {
+            let field = |name: &str| format!("dstl.entries.{}.{}", key, name);
+
+            let exec: String = config
+                .get(&field("exec"))
+                .map_err(|_| eyre!("dstl.entries.{} needs an exec command", key))?;
+
+            Ok(CustomEntry {
+                name: get_config_or(config, &field("name"), key.clone()),
+                exec,
+                category: get_config_or(config, &field("category"), default_category.to_string()),
+                terminal: get_config_or(config, &field("terminal"), false),
+                keywords: get_config_or(config, &field("keywords"), Vec::new()),
+                comment: config.get(&field("comment")).ok(),
+                icon: config.get(&field("icon")).ok(),
+                key,
+            })
+        })
+        .collect()
+}
+
+/// Read the `dstl.categories` blocks, which replace the built-in ruleset when present
+fn load_category_rules(config: &RuneConfig) -> CategoryRules {
+    let defaults = CategoryRules::default();
+    let fallback = get_config_or(config, "dstl.category_fallback", defaults.fallback.clone());
+
+    let Ok(keys) = config.get_keys("dstl.categories") else {
+        return CategoryRules { fallback, ..defaults };
+    };
+
+    let rules = keys
+        .into_iter()
+        .map(|key| {
+            let field = |name: &str| format!("dstl.categories.{}.{}", key, name);
+            CategoryRule {
+                name: get_config_or(config, &field("name"), key.clone()),
+                matches: get_config_or(config, &field("match"), Vec::new()),
+                contains: get_config_or(config, &field("contains"), Vec::new()),
+                apps: get_config_or(config, &field("apps"), Vec::new()),
+                priority: get_config_or(config, &field("priority"), 0i64),
+                icon: config.get(&field("icon")).ok(),
+            }
+        })
+        .collect();
+
+    CategoryRules { rules, fallback }
+}
+
 /// Load theme colors with priority system similar to claw
 fn load_theme_colors(config: &RuneConfig) -> Result<(String, String, String, String)> {
     let mut border = None;
@@ -247,20 +608,52 @@ fn load_theme_colors(config: &RuneConfig) -> Result<(String, String, String, Str
     Ok((border, focus, highlight, cursor))
 }
 
+/// Every setting at its default, for tests that need a config
+#[cfg(test)]
+pub fn default_config() -> DstlConfig {
+    let config = RuneConfig::from_str("").expect("an empty config parses");
+    extract_dstl_config(config).expect("the defaults are valid")
+}
+
+/// Overlay the `dstl.theme` values found in a standalone rune file
+pub fn apply_theme_file(path: &Path, theme: &mut LauncherTheme) -> Result<()> {
+    let config = RuneConfig::from_file(path)?;
+
+    theme.border = get_config_or(&config, "dstl.theme.border", theme.border.clone());
+    theme.focus = get_config_or(&config, "dstl.theme.focus", theme.focus.clone());
+    theme.highlight = get_config_or(&config, "dstl.theme.highlight", theme.highlight.clone());
+    theme.cursor_color = get_config_or(&config, "dstl.theme.cursor_color", theme.cursor_color.clone());
+    theme.border_style = get_config_or(&config, "dstl.theme.border_style", theme.border_style.clone());
+    theme.highlight_type = get_config_or(&config, "dstl.theme.highlight_type", theme.highlight_type.clone());
+    theme.details_label = get_config_or(&config, "dstl.theme.details_label", theme.details_label.clone());
+    theme.match_color = get_config_or(&config, "dstl.theme.match_color", theme.match_color.clone());
+    theme.match_style = get_config_or(&config, "dstl.theme.match_style", theme.match_style.clone());
+
+    Ok(())
+}
+
 /// Top-level config loader that exits gracefully on failure.
-pub fn load_launcher_config() -> DstlConfig {
-    let user_config = dirs::config_dir()
-        .map(|c| c.join("dstl/dstl.rune"))
-        .unwrap_or_else(|| PathBuf::from("~/.config/dstl/dstl.rune"));
-    
-    let system_config = PathBuf::from("/usr/share/doc/dstl/dstl.rune");
-    
-    // Load config with automatic import resolution and fallback support
-    let config = RuneConfig::from_file_with_fallback(&user_config, &system_config)
-        .unwrap_or_else(|e| {
-            eprintln!("❌ Configuration error:\n{}", e);
-            process::exit(1);
-        });
+///
+/// An explicit `path` (from `--config`) replaces the user/system lookup.
+pub fn load_launcher_config(path: Option<&Path>) -> DstlConfig {
+    let config = match path {
+        Some(path) => RuneConfig::from_file(path),
+        None => {
+            let user_config = dirs::config_dir()
+                .map(|c| c.join("dstl/dstl.rune"))
+                .unwrap_or_else(|| PathBuf::from("~/.config/dstl/dstl.rune"));
+
+            let system_config = PathBuf::from("/usr/share/doc/dstl/dstl.rune");
+
+            // Load config with automatic import resolution and fallback support
+            RuneConfig::from_file_with_fallback(&user_config, &system_config)
+        }
+    };
+
+    let config = config.unwrap_or_else(|e| {
+        eprintln!("❌ Configuration error:\n{}", e);
+        process::exit(1);
+    });
 
     // Extract DstlConfig from the loaded RuneConfig
     extract_dstl_config(config).unwrap_or_else(|e| {
@@ -268,3 +661,47 @@ pub fn load_launcher_config() -> DstlConfig {
         process::exit(1);
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn declared(categories: &[&str]) -> Vec<String> {
+        categories.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn default_rules_match_substrings() {
+        let rules = CategoryRules::default();
+        assert_eq!(rules.groups(&declared(&["X-GNOME-Settings-Panel"]), "a.desktop", "A"), ["Settings"]);
+        assert_eq!(rules.groups(&declared(&["ActionGame"]), "b.desktop", "B"), ["Games"]);
+        assert_eq!(rules.groups(&declared(&["Network", "ArcadeGame"]), "c.desktop", "C"), ["Games", "Network"]);
+        assert_eq!(rules.groups(&declared(&["Unknown"]), "d.desktop", "D"), ["Utilities"]);
+        assert_eq!(rules.groups(&declared(&["Game"]), "rofi.desktop", "Rofi"), ["Utilities"]);
+    }
+
+    #[test]
+    fn exclude_folds_non_ascii_names() {
+        let rules = ExcludeRules {
+            entries: vec!["éditeur".to_string()],
+            exec: Vec::new(),
+        };
+        assert!(rules.matches("editor.desktop", "Éditeur", "editor"));
+        assert!(!rules.matches("editor.desktop", "Editeur", "editor"));
+    }
+
+    #[test]
+    fn match_is_exact_and_contains_is_not() {
+        let rule = CategoryRule {
+            name: "Games".to_string(),
+            matches: vec!["Game".to_string()],
+            contains: vec!["puzzle".to_string()],
+            apps: Vec::new(),
+            priority: 0,
+            icon: None,
+        };
+        assert!(rule.claims("game"));
+        assert!(!rule.claims("ActionGame"));
+        assert!(rule.claims("X-PuzzleGame"));
+    }
+}
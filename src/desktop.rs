@@ -0,0 +1,539 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Data directories in XDG precedence order ($XDG_DATA_HOME first, then $XDG_DATA_DIRS)
+pub fn data_dirs() -> Vec<PathBuf> {
+    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("/home"));
+
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .unwrap_or_else(|| home.join(".local/share"));
+
+    let data_dirs = std::env::var_os("XDG_DATA_DIRS")
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".into());
+
+    let mut dirs = vec![data_home];
+    dirs.extend(std::env::split_paths(&data_dirs).filter(|p| p.is_absolute()));
+
+    // Flatpak, Nix and Snap export here, but sessions started outside a login
+    // shell often miss the profile snippets that add them to $XDG_DATA_DIRS
+    dirs.extend([
+        home.join(".local/share/flatpak/exports/share"),
+        PathBuf::from("/var/lib/flatpak/exports/share"),
+        home.join(".nix-profile/share"),
+        PathBuf::from("/nix/var/nix/profiles/default/share"),
+        PathBuf::from("/run/current-system/sw/share"),
+        PathBuf::from("/var/lib/snapd/desktop"),
+    ]);
+
+    let mut seen = HashSet::new();
+    dirs.retain(|d| seen.insert(d.clone()));
+    dirs
+}
+
+/// All .desktop files as (desktop-file ID, path), higher-precedence directories first.
+///
+/// Each ID is only returned once, so a user's override (including a `Hidden=true`
+/// one) shadows the system copy.
+pub fn desktop_files() -> Vec<(String, PathBuf)> {
+    desktop_files_in(&data_dirs())
+}
+
+fn desktop_files_in(data_dirs: &[PathBuf]) -> Vec<(String, PathBuf)> {
+    let mut files = Vec::new();
+    let mut seen_ids = HashSet::new();
+
+    for dir in data_dirs {
+        let apps_dir = dir.join("applications");
+        let mut found = Vec::new();
+        collect_desktop_files(&apps_dir, &apps_dir, &mut HashSet::new(), &mut found);
+        found.sort();
+
+        for (id, path) in found {
+            if seen_ids.insert(id.clone()) {
+                files.push((id, path));
+            }
+        }
+    }
+
+    files
+}
+
+/// Recursively gather .desktop files below `base`.
+///
+/// Symlinked directories are followed (Nix profiles are built from them), and
+/// `visited` holds the canonical paths already walked so a link back up can't loop.
+fn collect_desktop_files(
+    base: &Path,
+    dir: &Path,
+    visited: &mut HashSet<PathBuf>,
+    out: &mut Vec<(String, PathBuf)>,
+) {
+    let Ok(canonical) = fs::canonicalize(dir) else {
+        return;
+    };
+    if !visited.insert(canonical) {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        // is_dir() follows symlinks
+        if path.is_dir() {
+            collect_desktop_files(base, &path, visited, out);
+        } else if path.extension().and_then(|s| s.to_str()) == Some("desktop")
+            && let Some(id) = desktop_file_id(base, &path)
+        {
+            out.push((id, path));
+        }
+    }
+}
+
+/// Desktop-file ID: the path relative to `applications/` with `/` replaced by `-`
+/// (`foo/bar.desktop` → `foo-bar.desktop`)
+fn desktop_file_id(base: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(base).ok()?;
+    let parts: Vec<&str> = relative
+        .components()
+        .map(|c| c.as_os_str().to_str())
+        .collect::<Option<_>>()?;
+    Some(parts.join("-"))
+}
+
+/// Locale names to look for in `Key[locale]=` entries, most specific first.
+///
+/// Follows the Desktop Entry spec: `lang_COUNTRY.ENCODING@MODIFIER` from
+/// `LC_ALL`, `LC_MESSAGES` or `LANG` tries `lang_COUNTRY@MODIFIER`,
+/// `lang_COUNTRY`, `lang@MODIFIER` and then `lang` (the encoding is ignored).
+pub fn locale_candidates() -> Vec<String> {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|v| !v.is_empty())
+        .unwrap_or_default();
+
+    locale_fallbacks(&locale)
+}
+
+fn locale_fallbacks(locale: &str) -> Vec<String> {
+    if locale.is_empty() || locale == "C" || locale == "POSIX" {
+        return Vec::new();
+    }
+
+    let (rest, modifier) = match locale.split_once('@') {
+        Some((rest, modifier)) => (rest, Some(modifier)),
+        None => (locale, None),
+    };
+    let rest = rest.split('.').next().unwrap_or(rest);
+    let (lang, country) = match rest.split_once('_') {
+        Some((lang, country)) => (lang, Some(country)),
+        None => (rest, None),
+    };
+
+    let mut candidates = Vec::new();
+    if let (Some(country), Some(modifier)) = (country, modifier) {
+        candidates.push(format!("{}_{}@{}", lang, country, modifier));
+    }
+    if let Some(country) = country {
+        candidates.push(format!("{}_{}", lang, country));
+    }
+    if let Some(modifier) = modifier {
+        candidates.push(format!("{}@{}", lang, modifier));
+    }
+    candidates.push(lang.to_string());
+    candidates
+}
+
+/// Split a `;`-separated list value (`Keywords=`, `OnlyShowIn=`, ...)
+pub fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(';')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Split `Name[de_DE]` into `("Name", Some("de_DE"))`. A malformed `Name[de`
+/// comes back whole, so it matches no key instead of overwriting `Name`.
+pub fn split_locale_key(key: &str) -> (&str, Option<&str>) {
+    match key.split_once('[') {
+        Some((base, rest)) => match rest.strip_suffix(']') {
+            Some(locale) => (base.trim(), Some(locale)),
+            None => (key, None),
+        },
+        None => (key, None),
+    }
+}
+
+/// A translatable value: the untranslated string plus the best localized match
+#[derive(Debug, Default, Clone)]
+pub struct Localized {
+    default: Option<String>,
+    best: Option<(usize, String)>,
+}
+
+impl Localized {
+    /// Record a `Key=` or `Key[locale]=` value, keeping the best locale match
+    pub fn set(&mut self, locale: Option<&str>, value: &str, locales: &[String]) {
+        let Some(locale) = locale else {
+            self.default = Some(value.to_string());
+            return;
+        };
+
+        let Some(rank) = locales.iter().position(|l| l == locale) else {
+            return;
+        };
+
+        if self.best.as_ref().is_none_or(|(best, _)| rank < *best) {
+            self.best = Some((rank, value.to_string()));
+        }
+    }
+
+    /// The best localized value, falling back to the untranslated one
+    pub fn value(self) -> Option<String> {
+        self.best.map(|(_, v)| v).or(self.default)
+    }
+}
+
+/// Undo the string-level escapes every desktop entry value may use (`\s`, `\n`, `\t`, `\r`, `\\`)
+pub fn unescape_value(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => out.push(' '),
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some('\\') => out.push('\\'),
+            // Leave other escapes alone; the Exec quoting rules handle them
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+
+    out
+}
+
+/// Values the `%c`, `%i` and `%k` field codes expand to
+pub struct ExecContext<'a> {
+    pub name: &'a str,
+    pub icon: Option<&'a str>,
+    pub desktop_file: &'a Path,
+}
+
+/// Split an Exec value into argv following the Desktop Entry spec.
+///
+/// Handles double-quoted arguments with `\"`, `` \` ``, `\$` and `\\` escapes,
+/// `%%`, and the `%i`, `%c` and `%k` field codes. We never pass files or URLs,
+/// so `%f`/`%F`/`%u`/`%U` (and the deprecated codes) expand to nothing, and an
+/// argument consisting only of such a code, quoted or not, is dropped. Returns `None` for
+/// unterminated quotes or an empty command.
+pub fn parse_exec(exec: &str, ctx: &ExecContext) -> Option<Vec<String>> {
+    let exec = unescape_value(exec);
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut in_quotes = false;
+    // Whether the current argument had a field code that expanded to nothing
+    let mut dropped_code = false;
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                in_arg = true;
+            }
+            '\\' => {
+                current.push(chars.next()?);
+                in_arg = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if in_arg && !(current.is_empty() && dropped_code) {
+                    args.push(std::mem::take(&mut current));
+                }
+                in_arg = false;
+                dropped_code = false;
+            }
+            '%' => match chars.next()? {
+                '%' => {
+                    current.push('%');
+                    in_arg = true;
+                }
+                'c' => {
+                    current.push_str(ctx.name);
+                    in_arg = true;
+                }
+                'k' => {
+                    current.push_str(&ctx.desktop_file.to_string_lossy());
+                    in_arg = true;
+                }
+                // %i becomes two arguments, and only when there is an Icon
+                'i' if !in_quotes => {
+                    if let Some(icon) = ctx.icon {
+                        args.push("--icon".to_string());
+                        args.push(icon.to_string());
+                    }
+                }
+                _ => dropped_code = true,
+            },
+            c => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+
+    if in_quotes {
+        return None;
+    }
+    if in_arg && !(current.is_empty() && dropped_code) {
+        args.push(current);
+    }
+
+    (!args.is_empty()).then_some(args)
+}
+
+/// Peel the `env FOO=bar ...` prefix off an argv into separate assignments
+pub fn split_env(argv: Vec<String>) -> (Vec<(String, String)>, Vec<String>) {
+    if argv.first().map(String::as_str) != Some("env") {
+        return (Vec::new(), argv);
+    }
+
+    let assignments: Vec<(String, String)> = argv[1..]
+        .iter()
+        .map_while(|arg| {
+            let (key, value) = arg.split_once('=')?;
+            let valid = !key.is_empty()
+                && !key.starts_with(|c: char| c.is_ascii_digit())
+                && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            valid.then(|| (key.to_string(), value.to_string()))
+        })
+        .collect();
+
+    // `env` with flags or without a command is left for env itself to run
+    let rest = &argv[1 + assignments.len()..];
+    if rest.is_empty() || rest[0].starts_with('-') {
+        return (Vec::new(), argv);
+    }
+
+    (assignments, rest.to_vec())
+}
+
+/// Quote an argv back into a single line a shell would split the same way
+pub fn shell_join(argv: &[String]) -> String {
+    argv.iter()
+        .map(|arg| {
+            let safe = !arg.is_empty()
+                && arg.chars().all(|c| c.is_alphanumeric() || "-_./=:,+@%".contains(c));
+            if safe {
+                arg.clone()
+            } else {
+                format!("'{}'", arg.replace('\'', r"'\''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(icon: Option<&str>) -> ExecContext<'_> {
+        ExecContext {
+            name: "Text Editor",
+            icon,
+            desktop_file: Path::new("/usr/share/applications/editor.desktop"),
+        }
+    }
+
+    fn parse(exec: &str) -> Option<Vec<String>> {
+        parse_exec(exec, &ctx(None))
+    }
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    /// An empty scratch directory for one test
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("dstl-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn touch(path: &Path) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "[Desktop Entry]\n").unwrap();
+    }
+
+    fn ids(files: &[(String, PathBuf)]) -> Vec<&str> {
+        files.iter().map(|(id, _)| id.as_str()).collect()
+    }
+
+    #[test]
+    fn desktop_file_ids_join_subdirectories_with_dashes() {
+        let base = Path::new("/usr/share/applications");
+        assert_eq!(desktop_file_id(base, &base.join("firefox.desktop")).as_deref(), Some("firefox.desktop"));
+        assert_eq!(desktop_file_id(base, &base.join("kde4/foo.desktop")).as_deref(), Some("kde4-foo.desktop"));
+        assert_eq!(desktop_file_id(base, &base.join("a/b/c.desktop")).as_deref(), Some("a-b-c.desktop"));
+        assert_eq!(desktop_file_id(base, Path::new("/elsewhere/foo.desktop")), None);
+    }
+
+    #[test]
+    fn earlier_data_dirs_shadow_later_ones_by_id() {
+        let root = scratch_dir("shadow");
+        let (user, system) = (root.join("user"), root.join("system"));
+        touch(&user.join("applications/kde4-foo.desktop"));
+        touch(&system.join("applications/kde4/foo.desktop"));
+        touch(&system.join("applications/bar.desktop"));
+        touch(&system.join("applications/notes.txt"));
+
+        let files = desktop_files_in(&[user.clone(), system.clone()]);
+        assert_eq!(ids(&files), ["kde4-foo.desktop", "bar.desktop"]);
+        assert!(files[0].1.starts_with(&user));
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn symlinked_directories_are_followed_once() {
+        let root = scratch_dir("symlinks");
+        let apps = root.join("profile/applications");
+        touch(&root.join("store/vendor/app.desktop"));
+        fs::create_dir_all(&apps).unwrap();
+        std::os::unix::fs::symlink(root.join("store/vendor"), apps.join("vendor")).unwrap();
+        // A link back up must not recurse forever
+        std::os::unix::fs::symlink(&apps, root.join("store/vendor/loop")).unwrap();
+
+        let files = desktop_files_in(&[root.join("profile")]);
+        assert_eq!(ids(&files), ["vendor-app.desktop"]);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn locale_fallbacks_go_from_specific_to_language() {
+        assert_eq!(
+            locale_fallbacks("sr_RS.UTF-8@latin"),
+            ["sr_RS@latin", "sr_RS", "sr@latin", "sr"]
+        );
+        assert_eq!(locale_fallbacks("pt_BR.UTF-8"), ["pt_BR", "pt"]);
+        assert_eq!(locale_fallbacks("de@euro"), ["de@euro", "de"]);
+        assert_eq!(locale_fallbacks("fr"), ["fr"]);
+        assert!(locale_fallbacks("C").is_empty());
+        assert!(locale_fallbacks("POSIX").is_empty());
+        assert!(locale_fallbacks("").is_empty());
+    }
+
+    #[test]
+    fn locale_keys_need_a_closing_bracket() {
+        assert_eq!(split_locale_key("Name[de_DE]"), ("Name", Some("de_DE")));
+        assert_eq!(split_locale_key("Name"), ("Name", None));
+        assert_eq!(split_locale_key("Name[de"), ("Name[de", None));
+    }
+
+    #[test]
+    fn localized_prefers_the_most_specific_locale() {
+        let locales = locale_fallbacks("de_AT.UTF-8");
+        let mut name = Localized::default();
+        name.set(None, "Files", &locales);
+        name.set(Some("de"), "Dateien", &locales);
+        name.set(Some("de_AT"), "Dateien (AT)", &locales);
+        name.set(Some("fr"), "Fichiers", &locales);
+        assert_eq!(name.value().as_deref(), Some("Dateien (AT)"));
+    }
+
+    #[test]
+    fn quoted_args_keep_escaped_characters() {
+        // Exec values are unescaped once as strings first, hence the doubled backslashes
+        assert_eq!(
+            parse(r#"sh -c "echo \\"hi\\" \\\\ \\`x\\` \\$HOME""#),
+            Some(argv(&["sh", "-c", r#"echo "hi" \ `x` $HOME"#]))
+        );
+        assert_eq!(parse(r#"app "two words" ''"#), Some(argv(&["app", "two words", "''"])));
+        assert_eq!(parse(r#"app """#), Some(argv(&["app", ""])));
+    }
+
+    #[test]
+    fn percent_escapes_and_dropped_codes() {
+        assert_eq!(parse("printf 100%%"), Some(argv(&["printf", "100%"])));
+        assert_eq!(parse("editor %F --new"), Some(argv(&["editor", "--new"])));
+        assert_eq!(parse(r#"editor "%f" "%U""#), Some(argv(&["editor"])));
+        assert_eq!(parse("editor --file=%f"), Some(argv(&["editor", "--file="])));
+    }
+
+    #[test]
+    fn icon_code_needs_an_icon() {
+        assert_eq!(
+            parse_exec("editor %i", &ctx(Some("accessories-text-editor"))),
+            Some(argv(&["editor", "--icon", "accessories-text-editor"]))
+        );
+        assert_eq!(parse("editor %i"), Some(argv(&["editor"])));
+    }
+
+    #[test]
+    fn name_and_desktop_file_codes() {
+        assert_eq!(
+            parse(r#"editor --title "%c" %k"#),
+            Some(argv(&[
+                "editor",
+                "--title",
+                "Text Editor",
+                "/usr/share/applications/editor.desktop",
+            ]))
+        );
+    }
+
+    #[test]
+    fn invalid_exec_is_rejected() {
+        assert_eq!(parse(r#"sh -c "echo hi"#), None);
+        assert_eq!(parse("%f"), None);
+        assert_eq!(parse("   "), None);
+    }
+
+    #[test]
+    fn env_prefix_is_split_off() {
+        assert_eq!(
+            split_env(argv(&["env", "A=1", "B=2", "cmd", "C=3"])),
+            (
+                vec![("A".to_string(), "1".to_string()), ("B".to_string(), "2".to_string())],
+                argv(&["cmd", "C=3"])
+            )
+        );
+        assert_eq!(split_env(argv(&["cmd", "A=1"])), (Vec::new(), argv(&["cmd", "A=1"])));
+    }
+
+    #[test]
+    fn env_with_flags_or_no_command_is_left_alone() {
+        let with_flag = argv(&["env", "-i", "A=1", "cmd"]);
+        assert_eq!(split_env(with_flag.clone()), (Vec::new(), with_flag));
+        let no_command = argv(&["env", "A=1"]);
+        assert_eq!(split_env(no_command.clone()), (Vec::new(), no_command));
+    }
+
+    #[test]
+    fn shell_join_quotes_unsafe_args() {
+        assert_eq!(
+            shell_join(&argv(&["echo", "a b", "it's", "", "--x=1"])),
+            r"echo 'a b' 'it'\''s' '' --x=1"
+        );
+    }
+}
@@ -1,299 +1,462 @@
-use crossterm::event::KeyEvent;
-use crate::app::{App, Focus, Mode};
+use std::time::{Duration, Instant};
+use crossterm::event::{KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Position, Rect};
+use crate::app::{App, AppEntry, Focus, ListArea, Mode, SinglePaneMode, VimMode};
+use crate::clipboard;
 use crate::config::SearchPosition;
+use crate::keymap::Action;
 use eyre::Result;
 
 pub fn handle_key(app: &mut App, key: KeyEvent) -> Result<bool> {
+    // The desktop action sub-list grabs all keys while it's open
+    if app.action_menu.is_some() {
+        return handle_action_menu_key(app, key);
+    }
+
+    if app.vim_mode.is_some() {
+        return Ok(handle_vim_key(app, key));
+    }
+
+    // Typing goes to the query; bindings only see the keys the search bar doesn't use
+    if app.focus == Focus::Search && edit_query(app, key) {
+        return Ok(false);
+    }
+
+    match app.config.keys.action(key) {
+        Some(action) => Ok(run_action(app, action)),
+        None => Ok(false),
+    }
+}
+
+/// `dstl.vim_mode`: insert mode always types into the query, normal mode navigates
+fn handle_vim_key(app: &mut App, key: KeyEvent) -> bool {
     use crossterm::event::KeyCode::*;
 
-    match key.code {
-        Esc => return Ok(true),
-        Char('q') if app.focus != Focus::Search => return Ok(true),
-        Char('q') if app.focus == Focus::Search => {
-            // Insert 'q' at cursor position
-            let pos = app.cursor_position.min(app.search_query.len());
-            app.search_query.insert(pos, 'q');
-            app.cursor_position += 1;
-            update_selection_after_search(app);
+    if app.vim_mode == Some(VimMode::Insert) {
+        if key.code == Esc && key.modifiers.is_empty() {
+            app.vim_mode = Some(VimMode::Normal);
+            return false;
         }
+        // Typing reaches the query from any pane, but outside the search bar
+        // the arrows, Home and End keep moving through the lists
+        let moves_list = app.focus != Focus::Search && matches!(key.code, Left | Right | Home | End);
+        if !moves_list && edit_query(app, key) {
+            return false;
+        }
+        return app.config.keys.action(key).is_some_and(|action| run_action(app, action));
+    }
 
-        Enter => {
-            if let Some(app_entry) = get_selected_app(app) {
-                app.app_to_launch = Some(app_entry.exec.clone());
-                app.should_quit = true;
-                return Ok(true);
+    let pending = app.vim_pending.take();
+    if (key.modifiers - KeyModifiers::SHIFT).is_empty() {
+        match (pending, key.code) {
+            (Some('g'), Char('g')) => return jump_in_list(app, Action::First),
+            (Some('d'), Char('d')) => {
+                app.query.clear();
+                update_selection_after_search(app);
+                return false;
             }
+            (None, Char(c @ ('g' | 'd'))) => {
+                app.vim_pending = Some(c);
+                return false;
+            }
+            (_, Char('G')) => return jump_in_list(app, Action::Last),
+
+            // Vim's delete-char and put; hiding and pinning stay on Alt+X / Alt+P
+            (_, Char('x')) => {
+                app.query.delete_forward();
+                app.reset_cursor_blink();
+                update_selection_after_search(app);
+                return false;
+            }
+            (_, Char('p')) => {
+                if let Some(text) = clipboard::read() {
+                    handle_paste(app, &text);
+                    // A put doesn't leave normal mode
+                    app.vim_mode = Some(VimMode::Normal);
+                }
+                return false;
+            }
+
+            (_, Char('w')) => app.query.word_right(),
+            (_, Char('b')) => app.query.word_left(),
+            (_, Char('0')) => app.query.move_home(),
+            (_, Char('$')) => app.query.move_end(),
+            // h/l move along the query while it has focus, through the lists otherwise
+            (_, Char('h')) if app.focus == Focus::Search => app.query.move_left(),
+            (_, Char('l')) if app.focus == Focus::Search => app.query.move_right(),
+
+            (_, Char('i' | '/')) => enter_insert_mode(app),
+            (_, Char('a')) => {
+                enter_insert_mode(app);
+                app.query.move_right();
+            }
+
+            _ => return app.config.keys.action(key).is_some_and(|action| run_action(app, action)),
         }
+        app.reset_cursor_blink();
+        return false;
+    }
 
-        Char('m') if app.focus != Focus::Search => {
+    app.config.keys.action(key).is_some_and(|action| run_action(app, action))
+}
+
+/// `gg`/`G`: from the search bar they jump into the app list
+fn jump_in_list(app: &mut App, action: Action) -> bool {
+    if app.focus == Focus::Search {
+        app.focus = Focus::Apps;
+    }
+    run_action(app, action)
+}
+
+fn enter_insert_mode(app: &mut App) {
+    app.vim_mode = Some(VimMode::Insert);
+    app.focus = Focus::Search;
+}
+
+/// Apply a line-editing key to the search query; returns false for keys it doesn't handle
+fn edit_query(app: &mut App, key: KeyEvent) -> bool {
+    use crossterm::event::KeyCode::*;
+
+    const NONE: KeyModifiers = KeyModifiers::NONE;
+    const CTRL: KeyModifiers = KeyModifiers::CONTROL;
+    const ALT: KeyModifiers = KeyModifiers::ALT;
+
+    let before = app.query.text().to_string();
+    let editor = &mut app.query;
+
+    match (key.modifiers - KeyModifiers::SHIFT, key.code) {
+        (NONE, Char(c)) => editor.insert(c),
+
+        (NONE, Left) | (CTRL, Char('b')) => editor.move_left(),
+        (NONE, Right) | (CTRL, Char('f')) => editor.move_right(),
+        (NONE, Home) | (CTRL, Char('a')) => editor.move_home(),
+        (NONE, End) | (CTRL, Char('e')) => editor.move_end(),
+        (ALT, Char('b')) | (CTRL, Left) => editor.word_left(),
+        (ALT, Char('f')) | (CTRL, Right) => editor.word_right(),
+
+        (NONE, Backspace) => editor.delete_back(),
+        (NONE, Delete) | (CTRL, Char('d')) => editor.delete_forward(),
+        (CTRL, Char('w')) | (ALT, Backspace) => editor.kill_word_back(),
+        (ALT, Char('d')) => editor.kill_word_forward(),
+        (CTRL, Char('u')) => editor.kill_to_start(),
+        (CTRL, Char('k')) => editor.kill_to_end(),
+        (CTRL, Char('y')) => editor.yank(),
+
+        // Readline's Ctrl+_ and Ctrl+/ send the same byte, which crossterm reports as Ctrl+7
+        (CTRL, Char('z' | '_' | '/' | '7')) => editor.undo(),
+        (ALT, Char('z')) => editor.redo(),
+
+        _ => return false,
+    }
+
+    app.reset_cursor_blink(); // Keep cursor solid while editing
+    if app.query.text() != before {
+        update_selection_after_search(app);
+    }
+    true
+}
+
+/// Carry out a bound action; returns true when the launcher should exit
+fn run_action(app: &mut App, action: Action) -> bool {
+    match action {
+        Action::Quit => return true,
+
+        // Esc leaves the unhide view before it quits
+        Action::Back if app.show_hidden => app.toggle_hidden_view(),
+        Action::Back => return true,
+
+        Action::Launch => return launch_selected(app),
+        Action::ShowActions => open_action_menu(app),
+
+        Action::ToggleMode if app.single_pane_mode != SinglePaneMode::Dmenu => {
             app.toggle_mode();
             if app.config.focus_search_on_switch {
                 app.focus = Focus::Search;
             }
         }
+        Action::ToggleMode => {}
 
-        // Left/Right arrow keys for cursor movement in search
-        Left if app.focus == Focus::Search => {
-            if app.cursor_position > 0 {
-                app.cursor_position -= 1;
-                app.reset_cursor_blink(); // Keep cursor solid while moving
-            }
-        }
+        Action::FocusNext => cycle_focus(app, true),
+        Action::FocusPrev => cycle_focus(app, false),
 
-        Left if app.focus != Focus::Search => {
-            if app.mode == Mode::DualPane {
-                if app.focus == Focus::Apps {
-                    app.focus = Focus::Categories;
-                }
-            }
+        Action::Up => move_up(app),
+        Action::Down => move_down(app),
+        Action::Left => move_left(app),
+        Action::Right => move_right(app),
+
+        Action::PageUp => jump(app, |selected, _, page| selected.saturating_sub(page)),
+        Action::PageDown => jump(app, |selected, count, page| (selected + page).min(count - 1)),
+        Action::First => jump(app, |_, _, _| 0),
+        Action::Last => jump(app, |_, count, _| count - 1),
+
+        Action::ClearQuery => {
+            app.query.clear();
+            app.reset_cursor_blink();
+            update_selection_after_search(app);
         }
 
-        Right if app.focus == Focus::Search => {
-            let query_len = app.search_query.chars().count();
-            if app.cursor_position < query_len {
-                app.cursor_position += 1;
-                app.reset_cursor_blink(); // Keep cursor solid while moving
-            }
+        Action::TogglePin => toggle_selected_pin(app),
+        // x hides the selected app (or restores it in the unhide view), H lists the hidden ones
+        Action::ToggleHidden => toggle_selected_hidden(app),
+        Action::HiddenView => toggle_hidden_view(app),
+        Action::ToggleDetails => app.show_details = !app.show_details,
+
+        Action::PrevQuery | Action::NextQuery => {
+            app.recall_query(action == Action::PrevQuery);
+            app.focus = Focus::Search;
+            app.reset_cursor_blink();
+            update_selection_after_search(app);
         }
 
-        Right if app.focus != Focus::Search => {
-            if app.mode == Mode::DualPane {
-                if app.focus == Focus::Categories {
-                    app.focus = Focus::Apps;
-                }
+        Action::Paste => {
+            if let Some(text) = clipboard::read() {
+                handle_paste(app, &text);
             }
         }
+    }
 
-        // Home/End keys for jumping to start/end
-        Home if app.focus == Focus::Search => {
-            app.cursor_position = 0;
-            app.reset_cursor_blink(); // Keep cursor solid while moving
-        }
+    false
+}
 
-        End if app.focus == Focus::Search => {
-            app.cursor_position = app.search_query.chars().count();
-            app.reset_cursor_blink(); // Keep cursor solid while moving
-        }
+fn cycle_focus(app: &mut App, forward: bool) {
+    let order: &[Focus] = match app.mode {
+        Mode::SinglePane => &[Focus::Search, Focus::Apps],
+        Mode::DualPane => &[Focus::Search, Focus::Categories, Focus::Apps],
+    };
 
-        // Search input - insert at cursor position
-        Char(c) if app.focus == Focus::Search => {
-            let query_chars: Vec<char> = app.search_query.chars().collect();
-            let pos = app.cursor_position.min(query_chars.len());
-            
-            // Reconstruct string with new character inserted
-            let before: String = query_chars.iter().take(pos).collect();
-            let after: String = query_chars.iter().skip(pos).collect();
-            app.search_query = format!("{}{}{}", before, c, after);
-            
-            app.cursor_position += 1;
-            app.reset_cursor_blink(); // Keep cursor solid while typing
-            update_selection_after_search(app);
-        }
+    let current = order.iter().position(|&f| f == app.focus).unwrap_or(0);
+    let next = if forward {
+        (current + 1) % order.len()
+    } else {
+        (current + order.len() - 1) % order.len()
+    };
+    app.focus = order[next];
+}
 
-        Backspace if app.focus == Focus::Search => {
-            if app.cursor_position > 0 {
-                let query_chars: Vec<char> = app.search_query.chars().collect();
-                let pos = app.cursor_position - 1;
-                
-                if pos < query_chars.len() {
-                    // Reconstruct string without character at pos
-                    let before: String = query_chars.iter().take(pos).collect();
-                    let after: String = query_chars.iter().skip(pos + 1).collect();
-                    app.search_query = format!("{}{}", before, after);
-                    
-                    app.cursor_position -= 1;
-                    app.reset_cursor_blink(); // Keep cursor solid while deleting
-                    update_selection_after_search(app);
-                }
+/// Select the previous category; false when already at the first one
+fn previous_category(app: &mut App) -> bool {
+    let matching_categories = app.matching_category_indices();
+    if let Some(current_pos) = matching_categories.iter().position(|&idx| idx == app.selected_category)
+        && current_pos > 0
+    {
+        app.selected_category = matching_categories[current_pos - 1];
+        app.selected_app = 0;
+        return true;
+    }
+    false
+}
+
+/// Select the next category; false when already at the last one
+fn next_category(app: &mut App) -> bool {
+    let matching_categories = app.matching_category_indices();
+    if let Some(current_pos) = matching_categories.iter().position(|&idx| idx == app.selected_category)
+        && current_pos + 1 < matching_categories.len()
+    {
+        app.selected_category = matching_categories[current_pos + 1];
+        app.selected_app = 0;
+        return true;
+    }
+    false
+}
+
+/// Select the next app; false when already at the last one
+fn next_app(app: &mut App) -> bool {
+    let count = count_filtered_apps_in_current_category(app);
+    if count > 0 && app.selected_app + 1 < count {
+        app.selected_app += 1;
+        return true;
+    }
+    false
+}
+
+fn move_up(app: &mut App) {
+    let at_top = match app.focus {
+        // Allow Up from search to go to list only if search is at bottom
+        Focus::Search => {
+            if app.config.search_position == SearchPosition::Bottom {
+                app.focus = Focus::Apps;
             }
+            return;
+        }
+        Focus::Apps if app.selected_app > 0 => {
+            app.selected_app -= 1;
+            false
         }
+        Focus::Apps => true,
+        Focus::Categories => !previous_category(app),
+    };
 
-        // Delete key to remove character at cursor
-        Delete if app.focus == Focus::Search => {
-            let query_chars: Vec<char> = app.search_query.chars().collect();
-            if app.cursor_position < query_chars.len() {
-                // Reconstruct string without character at cursor_position
-                let before: String = query_chars.iter().take(app.cursor_position).collect();
-                let after: String = query_chars.iter().skip(app.cursor_position + 1).collect();
-                app.search_query = format!("{}{}", before, after);
-                
-                app.reset_cursor_blink(); // Keep cursor solid while deleting
-                update_selection_after_search(app);
+    if at_top && app.config.search_position == SearchPosition::Top {
+        app.focus = Focus::Search;
+    }
+}
+
+fn move_down(app: &mut App) {
+    let at_bottom = match app.focus {
+        // Allow Down from search to go to list only if search is at top
+        Focus::Search => {
+            if app.config.search_position == SearchPosition::Top {
+                app.focus = match app.mode {
+                    Mode::SinglePane => Focus::Apps,
+                    Mode::DualPane => Focus::Categories,
+                };
             }
+            return;
         }
+        Focus::Apps => !next_app(app),
+        Focus::Categories => !next_category(app),
+    };
 
-        Tab => {
-            app.focus = match app.mode {
-                Mode::SinglePane => match app.focus {
-                    Focus::Search => Focus::Apps,
-                    Focus::Apps | Focus::Categories => Focus::Search,
-                },
-                Mode::DualPane => match app.focus {
-                    Focus::Search => Focus::Categories,
-                    Focus::Categories => Focus::Apps,
-                    Focus::Apps => Focus::Search,
-                },
-            };
+    if at_bottom && app.config.search_position == SearchPosition::Bottom {
+        app.focus = Focus::Search;
+    }
+}
+
+/// Back to the categories in dual-pane, previous app in single-pane
+fn move_left(app: &mut App) {
+    match app.focus {
+        Focus::Apps if app.mode == Mode::DualPane => app.focus = Focus::Categories,
+        Focus::Apps => app.selected_app = app.selected_app.saturating_sub(1),
+        Focus::Categories => {
+            previous_category(app);
         }
+        Focus::Search => {}
+    }
+}
 
-        // Up/Down navigation
-        Up | Char('k') => {
-            if app.focus == Focus::Search {
-                // Allow Up from search to go to list only if search is at bottom
-                if app.config.search_position == SearchPosition::Bottom {
-                    app.focus = match app.mode {
-                        Mode::SinglePane => Focus::Apps,
-                        Mode::DualPane => Focus::Apps,
-                    };
-                }
-            } else {
-                // In list navigation
-                match app.mode {
-                    Mode::SinglePane => {
-                        match app.focus {
-                            Focus::Apps => {
-                                if app.selected_app > 0 {
-                                    app.selected_app -= 1;
-                                } else if app.config.search_position == SearchPosition::Top {
-                                    app.focus = Focus::Search;
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-
-                    Mode::DualPane => {
-                        match app.focus {
-                            Focus::Apps => {
-                                if app.selected_app > 0 {
-                                    app.selected_app -= 1;
-                                } else if app.config.search_position == SearchPosition::Top {
-                                    app.focus = Focus::Search;
-                                }
-                            }
-                            Focus::Categories => {
-                                let matching_categories = get_matching_category_indices(app);
-                                if let Some(current_pos) = matching_categories.iter().position(|&idx| idx == app.selected_category) {
-                                    if current_pos > 0 {
-                                        app.selected_category = matching_categories[current_pos - 1];
-                                        app.selected_app = 0;
-                                    } else if app.config.search_position == SearchPosition::Top {
-                                        app.focus = Focus::Search;
-                                    }
-                                } else if app.config.search_position == SearchPosition::Top {
-                                    app.focus = Focus::Search;
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-            }
+/// Into the apps in dual-pane, next app in single-pane; opens the desktop actions if there are any
+fn move_right(app: &mut App) {
+    match app.focus {
+        Focus::Apps if app.selected_entry().is_some_and(|a| !a.actions.is_empty()) => {
+            open_action_menu(app);
+        }
+        Focus::Apps => {
+            next_app(app);
+        }
+        Focus::Categories if app.mode == Mode::DualPane => app.focus = Focus::Apps,
+        Focus::Categories => {
+            next_category(app);
         }
+        Focus::Search => {}
+    }
+}
 
-        Down | Char('j') => {
-            if app.focus == Focus::Search {
-                // Allow Down from search to go to list only if search is at top
-                if app.config.search_position == SearchPosition::Top {
-                    app.focus = match app.mode {
-                        Mode::SinglePane => Focus::Apps,
-                        Mode::DualPane => Focus::Categories,
-                    };
-                }
-            } else {
-                // In list navigation
-                match app.mode {
-                    Mode::SinglePane => {
-                        match app.focus {
-                            Focus::Apps => {
-                                let count = count_filtered_apps_in_current_category(app);
-                                if count > 0 && app.selected_app + 1 < count {
-                                    app.selected_app += 1;
-                                } else if app.config.search_position == SearchPosition::Bottom {
-                                    app.focus = Focus::Search;
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-
-                    Mode::DualPane => {
-                        match app.focus {
-                            Focus::Categories => {
-                                let matching_categories = get_matching_category_indices(app);
-                                if let Some(current_pos) = matching_categories.iter().position(|&idx| idx == app.selected_category) {
-                                    if current_pos + 1 < matching_categories.len() {
-                                        app.selected_category = matching_categories[current_pos + 1];
-                                        app.selected_app = 0;
-                                    } else if app.config.search_position == SearchPosition::Bottom {
-                                        app.focus = Focus::Search;
-                                    }
-                                } else if app.config.search_position == SearchPosition::Bottom {
-                                    app.focus = Focus::Search;
-                                }
-                            }
-                            Focus::Apps => {
-                                let count = count_filtered_apps_in_current_category(app);
-                                if count > 0 && app.selected_app + 1 < count {
-                                    app.selected_app += 1;
-                                } else if app.config.search_position == SearchPosition::Bottom {
-                                    app.focus = Focus::Search;
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
+/// Rows the list under focus shows at once
+fn page_size(app: &App) -> usize {
+    let area = match app.focus {
+        Focus::Categories => app.areas.categories.map(|c| c.area).unwrap_or_default(),
+        _ => app.areas.apps.area,
+    };
+    (area.height.saturating_sub(2) as usize).max(1)
+}
+
+/// Move the selection of the focused list to `target(selected, count, page)`
+fn jump(app: &mut App, target: impl Fn(usize, usize, usize) -> usize) {
+    let page = page_size(app);
+
+    match app.focus {
+        Focus::Apps => {
+            let count = count_filtered_apps_in_current_category(app);
+            if count > 0 {
+                app.selected_app = target(app.selected_app, count, page);
+            }
+        }
+        Focus::Categories => {
+            let matching = app.matching_category_indices();
+            if let Some(current) = matching.iter().position(|&idx| idx == app.selected_category) {
+                let next = target(current, matching.len(), page);
+                if next != current {
+                    app.selected_category = matching[next];
+                    app.selected_app = 0;
                 }
             }
         }
+        Focus::Search => {}
+    }
+}
 
-        // h/l keys only work for list navigation when NOT in search
-        Char('h') if app.focus != Focus::Search => {
-            match app.focus {
-                Focus::Apps => {
-                    if app.mode == Mode::DualPane {
-                        app.focus = Focus::Categories;
-                    } else {
-                        if app.selected_app > 0 {
-                            app.selected_app -= 1;
-                        }
-                    }
-                }
-                Focus::Categories => {
-                    let matching_categories = get_matching_category_indices(app);
-                    if let Some(current_pos) = matching_categories.iter().position(|&idx| idx == app.selected_category) {
-                        if current_pos > 0 {
-                            app.selected_category = matching_categories[current_pos - 1];
-                            app.selected_app = 0;
-                        }
-                    }
+/// Insert pasted text at the cursor; line breaks become spaces so a paste never launches anything
+pub fn handle_paste(app: &mut App, text: &str) {
+    if app.action_menu.is_some() {
+        return;
+    }
+
+    let text: String = text
+        .trim_end_matches(['\r', '\n'])
+        .replace("\r\n", "\n")
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+
+    app.focus = Focus::Search;
+    if app.vim_mode.is_some() {
+        app.vim_mode = Some(VimMode::Insert);
+    }
+    app.query.insert_str(&text);
+    app.reset_cursor_blink();
+    update_selection_after_search(app);
+}
+
+/// Pick the selected entry and quit; returns whether there was one
+fn launch_selected(app: &mut App) -> bool {
+    if let Some(app_entry) = app.selected_entry() {
+        app.app_to_launch = Some(app_entry.clone());
+        app.should_quit = true;
+        return true;
+    }
+
+    // dmenu prints the typed text when nothing matches
+    if app.single_pane_mode == SinglePaneMode::Dmenu && !app.query.is_empty() {
+        app.app_to_launch = Some(AppEntry::from_line(app.query.text().to_string()));
+        app.should_quit = true;
+        return true;
+    }
+
+    false
+}
+
+/// Pick the selected desktop action of the open action menu and quit
+fn launch_selected_action(app: &mut App) -> bool {
+    let Some(entry) = app.action_menu.take() else {
+        return false;
+    };
+    app.action_to_launch = entry.actions.get(app.selected_action).cloned();
+    app.app_to_launch = Some(entry);
+    app.should_quit = true;
+    true
+}
+
+/// Two clicks on the same row within this long launch it
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+pub fn handle_mouse(app: &mut App, mouse: MouseEvent) -> Result<bool> {
+    let pos = Position::new(mouse.column, mouse.row);
+    let areas = app.areas;
+
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            if areas.search.contains(pos) {
+                click_search_bar(app, areas.search, pos);
+            } else if let Some(row) = areas.categories.and_then(|c| list_row(c, pos)) {
+                if let Some(&idx) = app.matching_category_indices().get(row) {
+                    close_action_menu(app);
+                    app.selected_category = idx;
+                    app.selected_app = 0;
+                    app.focus = Focus::Categories;
                 }
-                _ => {}
+            } else if let Some(row) = list_row(areas.apps, pos) {
+                return Ok(click_app_row(app, row));
             }
         }
 
-        Char('l') if app.focus != Focus::Search => {
-            match app.focus {
-                Focus::Categories => {
-                    if app.mode == Mode::DualPane {
-                        app.focus = Focus::Apps;
-                    } else {
-                        let matching_categories = get_matching_category_indices(app);
-                        if let Some(current_pos) = matching_categories.iter().position(|&idx| idx == app.selected_category) {
-                            if current_pos + 1 < matching_categories.len() {
-                                app.selected_category = matching_categories[current_pos + 1];
-                                app.selected_app = 0;
-                            }
-                        }
-                    }
-                }
-                Focus::Apps => {
-                    let count = count_filtered_apps_in_current_category(app);
-                    if count > 0 && app.selected_app + 1 < count {
-                        app.selected_app += 1;
-                    }
-                }
-                _ => {}
+        // The wheel moves the selection of whichever list is under the pointer
+        MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+            let down = mouse.kind == MouseEventKind::ScrollDown;
+            if areas.categories.is_some_and(|c| c.area.contains(pos)) {
+                close_action_menu(app);
+                scroll_categories(app, down);
+            } else if areas.apps.area.contains(pos) {
+                scroll_apps(app, down);
             }
         }
 
@@ -303,35 +466,139 @@ pub fn handle_key(app: &mut App, key: KeyEvent) -> Result<bool> {
     Ok(false)
 }
 
-fn get_matching_category_indices(app: &App) -> Vec<usize> {
-    if app.search_query.is_empty() {
-        (0..app.categories.len()).collect()
+/// Row index a click landed on, counting the rows scrolled out of view
+fn list_row(list: ListArea, pos: Position) -> Option<usize> {
+    let inner = Rect {
+        x: list.area.x + 1,
+        y: list.area.y + 1,
+        width: list.area.width.saturating_sub(2),
+        height: list.area.height.saturating_sub(2),
+    };
+    inner
+        .contains(pos)
+        .then(|| list.offset + (pos.y - inner.y) as usize)
+}
+
+/// Focus the search bar and move the cursor to the clicked character
+fn click_search_bar(app: &mut App, area: Rect, pos: Position) {
+    // Keys and pastes go to the action menu while it's open
+    close_action_menu(app);
+    app.focus = Focus::Search;
+    if app.vim_mode.is_some() {
+        app.vim_mode = Some(VimMode::Insert);
+    }
+
+    // Same scrolling as render_search_bar: borders plus a space of padding each side
+    let available_width = area.width.saturating_sub(4) as usize;
+    let column = pos.x.saturating_sub(area.x + 2) as usize;
+
+    app.query.click(available_width, column);
+    app.reset_cursor_blink();
+}
+
+/// Select the clicked app (or action); a double-click launches it
+fn click_app_row(app: &mut App, row: usize) -> bool {
+    let count = match app.action_menu {
+        Some(ref entry) => entry.actions.len(),
+        None => count_filtered_apps_in_current_category(app),
+    };
+    if row >= count {
+        return false;
+    }
+
+    let now = Instant::now();
+    let double = app
+        .last_click
+        .is_some_and(|(at, last_row)| last_row == row && now.duration_since(at) <= DOUBLE_CLICK);
+    app.last_click = Some((now, row));
+    app.focus = Focus::Apps;
+
+    if app.action_menu.is_some() {
+        app.selected_action = row;
+        return double && launch_selected_action(app);
+    }
+
+    app.selected_app = row;
+    double && launch_selected(app)
+}
+
+fn scroll_apps(app: &mut App, down: bool) {
+    let (selected, count) = match app.action_menu {
+        Some(ref entry) => (&mut app.selected_action, entry.actions.len()),
+        None => {
+            let count = count_filtered_apps_in_current_category(app);
+            (&mut app.selected_app, count)
+        }
+    };
+
+    if down {
+        if *selected + 1 < count {
+            *selected += 1;
+        }
     } else {
-        let query_lower = app.search_query.to_lowercase();
-        app.categories
-            .iter()
-            .enumerate()
-            .filter(|(_, cat_name)| {
-                if *cat_name == "Recent" {
-                    app.recent_apps.iter().any(|recent_name| {
-                        app.apps.iter()
-                            .find(|a| &a.name == recent_name)
-                            .and_then(|a| app.matches_search(&a.name, &query_lower))
-                            .is_some()
-                    })
-                } else {
-                    app.apps.iter().any(|a| {
-                        &a.category == *cat_name && app.matches_search(&a.name, &query_lower).is_some()
-                    })
-                }
-            })
-            .map(|(idx, _)| idx)
-            .collect()
+        *selected = selected.saturating_sub(1);
+    }
+}
+
+fn scroll_categories(app: &mut App, down: bool) {
+    let matching = app.matching_category_indices();
+    let Some(current) = matching.iter().position(|&idx| idx == app.selected_category) else {
+        return;
+    };
+
+    let next = if down {
+        (current + 1).min(matching.len() - 1)
+    } else {
+        current.saturating_sub(1)
+    };
+    if next != current {
+        app.selected_category = matching[next];
+        app.selected_app = 0;
+    }
+}
+
+/// Show the selected app's desktop actions in place of the app list
+fn open_action_menu(app: &mut App) {
+    if let Some(entry) = app.selected_entry().filter(|a| !a.actions.is_empty()).cloned() {
+        app.action_menu = Some(entry);
+        app.selected_action = 0;
+        app.focus = Focus::Apps;
+        app.last_click = None;
     }
 }
 
+/// Back to the app list. Row numbers now mean other entries, so the next click
+/// can't complete a double-click started in the menu.
+fn close_action_menu(app: &mut App) {
+    app.action_menu = None;
+    app.last_click = None;
+}
+
+fn handle_action_menu_key(app: &mut App, key: KeyEvent) -> Result<bool> {
+    let count = app.action_menu.as_ref().map_or(0, |entry| entry.actions.len());
+    let page = page_size(app);
+
+    match app.config.keys.action(key) {
+        Some(Action::Back | Action::Left) => close_action_menu(app),
+        Some(Action::Quit) => return Ok(true),
+
+        Some(Action::Up) => app.selected_action = app.selected_action.saturating_sub(1),
+        Some(Action::Down) if app.selected_action + 1 < count => app.selected_action += 1,
+        Some(Action::PageUp) => app.selected_action = app.selected_action.saturating_sub(page),
+        Some(Action::PageDown) => app.selected_action = (app.selected_action + page).min(count.saturating_sub(1)),
+        Some(Action::First) => app.selected_action = 0,
+        Some(Action::Last) => app.selected_action = count.saturating_sub(1),
+
+        Some(Action::Launch) => return Ok(launch_selected_action(app)),
+
+        _ => {}
+    }
+
+    Ok(false)
+}
+
 fn update_selection_after_search(app: &mut App) {
-    if app.search_query.is_empty() {
+    if app.query.is_empty() {
         app.selected_category = 0;
         app.selected_app = 0;
         return;
@@ -339,7 +606,7 @@ fn update_selection_after_search(app: &mut App) {
 
     match app.mode {
         Mode::DualPane => {
-            let matching_indices = get_matching_category_indices(app);
+            let matching_indices = app.matching_category_indices();
             if let Some(&first_match) = matching_indices.first() {
                 app.selected_category = first_match;
                 app.selected_app = 0;
@@ -349,47 +616,6 @@ fn update_selection_after_search(app: &mut App) {
     }
 }
 
-fn get_selected_app(app: &App) -> Option<&crate::app::AppEntry> {
-    match app.mode {
-        Mode::SinglePane => {
-            app.visible_apps().get(app.selected_app).map(|v| &**v)
-        }
-        Mode::DualPane => {
-            let cat_name = app.categories.get(app.selected_category)?;
-            
-            if cat_name == "Recent" {
-                let apps_in_order: Vec<&crate::app::AppEntry> = app.recent_apps.iter()
-                    .filter_map(|recent_name| {
-                        app.apps.iter().find(|a| &a.name == recent_name)
-                    })
-                    .collect();
-                
-                if !app.search_query.is_empty() {
-                    let mut apps_with_scores: Vec<(&crate::app::AppEntry, i64)> = apps_in_order
-                        .into_iter()
-                        .filter_map(|a| app.matches_search(&a.name, &app.search_query).map(|score| (a, score)))
-                        .collect();
-                    apps_with_scores.sort_by(|a, b| b.1.cmp(&a.1));
-                    return apps_with_scores.get(app.selected_app).map(|(entry, _)| *entry);
-                }
-                
-                apps_in_order.get(app.selected_app).copied()
-            } else {
-                let mut apps_with_scores: Vec<(&crate::app::AppEntry, i64)> = app.apps.iter()
-                    .filter(|a| &a.category == cat_name)
-                    .filter_map(|a| app.matches_search(&a.name, &app.search_query).map(|score| (a, score)))
-                    .collect();
-
-                if !app.search_query.is_empty() {
-                    apps_with_scores.sort_by(|a, b| b.1.cmp(&a.1));
-                }
-
-                apps_with_scores.get(app.selected_app).map(|(entry, _)| *entry)
-            }
-        }
-    }
-}
-
 fn count_filtered_apps_in_current_category(app: &App) -> usize {
     match app.mode {
         Mode::SinglePane => {
@@ -400,20 +626,46 @@ fn count_filtered_apps_in_current_category(app: &App) -> usize {
                 Some(c) => c,
                 None => return 0,
             };
-            
-            if cat_name == "Recent" {
-                app.recent_apps.iter()
-                    .filter_map(|recent_name| {
-                        app.apps.iter().find(|a| &a.name == recent_name)
-                    })
-                    .filter(|a| app.matches_search(&a.name, &app.search_query).is_some())
-                    .count()
-            } else {
-                app.apps.iter()
-                    .filter(|a| &a.category == cat_name)
-                    .filter(|a| app.matches_search(&a.name, &app.search_query).is_some())
-                    .count()
-            }
+            app.visible_apps_in_category(cat_name).len()
         }
     }
 }
+
+/// Pin the selected app to Favorites, or unpin it
+fn toggle_selected_pin(app: &mut App) {
+    if app.single_pane_mode == SinglePaneMode::Dmenu {
+        return;
+    }
+    if let Some(id) = app.selected_entry().map(|a| a.id.clone()) {
+        app.toggle_pin(&id);
+        clamp_selection(app);
+    }
+}
+
+/// Hide the selected app, or restore it when the unhide view is showing
+fn toggle_selected_hidden(app: &mut App) {
+    if app.single_pane_mode == SinglePaneMode::Dmenu {
+        return;
+    }
+    if let Some(id) = app.selected_entry().map(|a| a.id.clone()) {
+        app.toggle_hidden(&id);
+        clamp_selection(app);
+    }
+}
+
+fn toggle_hidden_view(app: &mut App) {
+    if app.single_pane_mode != SinglePaneMode::Dmenu {
+        app.toggle_hidden_view();
+    }
+}
+
+/// Keep the selection in range after the current list shrank
+fn clamp_selection(app: &mut App) {
+    let categories = app.matching_category_indices();
+    if !categories.contains(&app.selected_category) {
+        app.selected_category = categories.first().copied().unwrap_or(0);
+    }
+
+    let count = count_filtered_apps_in_current_category(app);
+    app.selected_app = app.selected_app.min(count.saturating_sub(1));
+}
@@ -0,0 +1,406 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+
+/// Launch timestamps kept per entry for the frecency average
+const MAX_SAMPLES: usize = 10;
+/// Entries kept in the database; the least recently used are dropped first
+const MAX_ENTRIES: usize = 500;
+/// Searches kept for recall and ranking
+const MAX_QUERIES: usize = 100;
+/// Shortest typed prefix that brings back a learned entry; one letter only counts as a whole query
+const MIN_LEARNED_PREFIX: usize = 2;
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// How often and how recently an entry was launched
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Usage {
+    pub count: u32,
+    pub last_used: u64,
+    /// Most recent launches first, at most MAX_SAMPLES
+    pub timestamps: Vec<u64>,
+}
+
+/// A search that ended in a launch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryRecord {
+    pub query: String,
+    /// ID of the entry launched from it
+    pub entry: String,
+}
+
+/// On-disk format version of history.json
+const FORMAT_VERSION: u32 = 1;
+
+/// Persistent usage database backing the Recent category and frecency ranking.
+///
+/// Keyed by a stable entry ID (desktop-file ID, or absolute path in run mode)
+/// and stored as `$XDG_STATE_HOME/dstl/history.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct History {
+    pub version: u32,
+    pub entries: HashMap<String, Usage>,
+    /// IDs pinned to the Favorites category, in pinning order
+    #[serde(default)]
+    pub pinned: Vec<String>,
+    /// IDs hidden from the lists from inside the launcher
+    #[serde(default)]
+    pub hidden: Vec<String>,
+    /// Searches that ended in a launch, most recent first, each query once
+    #[serde(default)]
+    pub queries: Vec<QueryRecord>,
+    /// Set when history.json doesn't exist yet, so the legacy files get imported
+    #[serde(skip)]
+    fresh: bool,
+    /// Set when history.json couldn't be read or moved aside, so saving must not replace it
+    #[serde(skip)]
+    read_only: bool,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            entries: HashMap::new(),
+            pinned: Vec::new(),
+            hidden: Vec::new(),
+            queries: Vec::new(),
+            fresh: false,
+            read_only: false,
+        }
+    }
+}
+
+impl History {
+    /// Load the usage database from the state directory
+    pub fn load() -> Self {
+        Self::load_from(&state_dir().join("history.json"))
+    }
+
+    fn load_from(path: &Path) -> Self {
+        let json = match fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Self { fresh: true, ..Self::default() };
+            }
+            Err(_) => return Self::set_aside(path),
+        };
+
+        match serde_json::from_str::<Self>(&json) {
+            Ok(history) if history.version == FORMAT_VERSION => history,
+            // Unreadable or unknown version: start over rather than misread it
+            _ => Self::set_aside(path),
+        }
+    }
+
+    /// Start from an empty database, first moving the file we couldn't use to
+    /// history.json.bak; if that fails, leave it alone and never save over it
+    fn set_aside(path: &Path) -> Self {
+        let moved = fs::rename(path, path.with_extension("json.bak")).is_ok();
+        Self { read_only: !moved, ..Self::default() }
+    }
+
+    /// Save the usage database to disk
+    pub fn save(&self) -> io::Result<()> {
+        if self.read_only {
+            return Err(io::Error::other("history.json could not be read, not overwriting it"));
+        }
+        let dir = state_dir();
+        fs::create_dir_all(&dir)?;
+
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(dir.join("history.json"), json)?;
+        Ok(())
+    }
+
+    /// Whether there may be a name-keyed ~/.cache/dstl/recent.json to import
+    pub fn needs_migration(&self) -> bool {
+        self.fresh && legacy_dir().join("recent.json").exists()
+    }
+
+    /// Import the old ~/.cache/dstl/recent.json name list, mapping display
+    /// names to IDs with `resolve`. Names that no longer resolve are dropped.
+    pub fn migrate_legacy<F: Fn(&str) -> Option<String>>(&mut self, resolve: F) {
+        self.import_recent(&legacy_dir().join("recent.json"), resolve);
+    }
+
+    fn import_recent<F: Fn(&str) -> Option<String>>(&mut self, path: &Path, resolve: F) {
+        self.fresh = false;
+
+        let Ok(json) = fs::read_to_string(path) else {
+            return;
+        };
+        let names: Vec<String> = serde_json::from_str(&json).unwrap_or_default();
+        let now = now();
+
+        // Keep the old most-recent-first order by spacing the launches a second apart
+        for (i, name) in names.into_iter().enumerate() {
+            let Some(id) = resolve(&name) else {
+                continue;
+            };
+            let at = now.saturating_sub(i as u64);
+            self.entries
+                .entry(id)
+                .or_insert(Usage { count: 1, last_used: at, timestamps: vec![at] });
+        }
+    }
+
+    /// Record a launch of `key` right now
+    pub fn record(&mut self, key: &str) {
+        let now = now();
+        let usage = self.entries.entry(key.to_string()).or_default();
+        usage.count += 1;
+        usage.last_used = now;
+        usage.timestamps.insert(0, now);
+        usage.timestamps.truncate(MAX_SAMPLES);
+
+        if self.entries.len() > MAX_ENTRIES {
+            let mut by_age: Vec<(String, u64)> = self.entries
+                .iter()
+                .map(|(k, u)| (k.clone(), u.last_used))
+                .collect();
+            by_age.sort_by_key(|(_, last_used)| *last_used);
+            for (key, _) in by_age.into_iter().take(self.entries.len() - MAX_ENTRIES) {
+                self.entries.remove(&key);
+            }
+        }
+    }
+
+    /// Remember that searching for `query` launched `key`
+    pub fn record_query(&mut self, query: &str, key: &str) {
+        let query = query.trim();
+        if query.is_empty() {
+            return;
+        }
+
+        self.queries.retain(|q| q.query != query);
+        self.queries.insert(0, QueryRecord { query: query.to_string(), entry: key.to_string() });
+        self.queries.truncate(MAX_QUERIES);
+    }
+
+    /// The entry launched last from a search that began with `prefix`, or that was
+    /// exactly `prefix` when it's shorter than MIN_LEARNED_PREFIX
+    pub fn learned(&self, prefix: &str) -> Option<&str> {
+        if prefix.is_empty() {
+            return None;
+        }
+        let long_enough = prefix.chars().count() >= MIN_LEARNED_PREFIX;
+
+        self.queries
+            .iter()
+            .find(|q| {
+                q.query
+                    .get(..prefix.len())
+                    .is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+                    && (long_enough || q.query.len() == prefix.len())
+            })
+            .map(|q| q.entry.as_str())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Usage> {
+        self.entries.get(key)
+    }
+
+    pub fn is_pinned(&self, key: &str) -> bool {
+        self.pinned.iter().any(|k| k == key)
+    }
+
+    /// Pin `key` if it isn't pinned yet, unpin it otherwise
+    pub fn toggle_pin(&mut self, key: &str) {
+        toggle(&mut self.pinned, key);
+    }
+
+    pub fn is_hidden(&self, key: &str) -> bool {
+        self.hidden.iter().any(|k| k == key)
+    }
+
+    /// Hide `key` if it's listed, bring it back otherwise
+    pub fn toggle_hidden(&mut self, key: &str) {
+        toggle(&mut self.hidden, key);
+    }
+
+    /// Frecency score: launch count times the average recency weight of the
+    /// sampled launches (recent launches weigh more, old ones fade out)
+    pub fn frecency(&self, key: &str) -> f64 {
+        let Some(usage) = self.entries.get(key) else {
+            return 0.0;
+        };
+        if usage.timestamps.is_empty() {
+            return 0.0;
+        }
+
+        let now = now();
+        let total: f64 = usage.timestamps
+            .iter()
+            .map(|&t| match now.saturating_sub(t) {
+                age if age < 4 * DAY => 100.0,
+                age if age < 14 * DAY => 70.0,
+                age if age < 31 * DAY => 50.0,
+                age if age < 90 * DAY => 30.0,
+                _ => 10.0,
+            })
+            .sum();
+
+        usage.count as f64 * total / usage.timestamps.len() as f64
+    }
+
+    /// All keys, highest frecency first (most recent launch breaks ties)
+    pub fn ranked(&self) -> Vec<String> {
+        let mut keys: Vec<(&String, f64, u64)> = self.entries
+            .iter()
+            .map(|(k, u)| (k, self.frecency(k), u.last_used))
+            .collect();
+        keys.sort_by(|a, b| b.1.total_cmp(&a.1).then(b.2.cmp(&a.2)));
+        keys.into_iter().map(|(k, _, _)| k.clone()).collect()
+    }
+}
+
+/// Add `key` to `list`, or remove it if it's already there
+fn toggle(list: &mut Vec<String>, key: &str) {
+    if list.iter().any(|k| k == key) {
+        list.retain(|k| k != key);
+    } else {
+        list.push(key.to_string());
+    }
+}
+
+fn state_dir() -> PathBuf {
+    dirs::state_dir()
+        .or_else(|| dirs::home_dir().map(|h| h.join(".local/state")))
+        .map(|p| p.join("dstl"))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Where dstl kept name-keyed history before history.json
+fn legacy_dir() -> PathBuf {
+    dirs::cache_dir()
+        .map(|p| p.join("dstl"))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Seconds since the Unix epoch
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty scratch directory for one test
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("dstl-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn recent_json_is_imported_in_order() {
+        let dir = scratch_dir("import");
+        let path = dir.join("recent.json");
+        fs::write(&path, r#"["Firefox", "Removed App", "Files"]"#).unwrap();
+
+        let mut history = History { fresh: true, ..History::default() };
+        history.import_recent(&path, |name| match name {
+            "Firefox" => Some("firefox.desktop".to_string()),
+            "Files" => Some("org.gnome.Nautilus.desktop".to_string()),
+            _ => None,
+        });
+
+        assert!(!history.fresh);
+        assert_eq!(history.entries.len(), 2);
+        assert_eq!(history.ranked(), ["firefox.desktop", "org.gnome.Nautilus.desktop"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn unreadable_history_is_moved_aside() {
+        let dir = scratch_dir("corrupt");
+        let path = dir.join("history.json");
+        fs::write(&path, "{ not json").unwrap();
+
+        let history = History::load_from(&path);
+        assert!(!history.read_only);
+        assert!(history.entries.is_empty());
+        assert!(!path.exists());
+        assert_eq!(fs::read_to_string(dir.join("history.json.bak")).unwrap(), "{ not json");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn newer_history_that_cant_be_moved_is_never_saved_over() {
+        let dir = scratch_dir("newer");
+        let path = dir.join("history.json");
+        let json = r#"{"version": 99, "entries": {}}"#;
+        fs::write(&path, json).unwrap();
+        // A directory in the way makes the rename fail
+        fs::create_dir(dir.join("history.json.bak")).unwrap();
+        fs::write(dir.join("history.json.bak/keep"), "").unwrap();
+
+        let history = History::load_from(&path);
+        assert!(history.read_only);
+        assert!(history.save().is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), json);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    fn queries(history: &History) -> Vec<(&str, &str)> {
+        history.queries.iter().map(|q| (q.query.as_str(), q.entry.as_str())).collect()
+    }
+
+    #[test]
+    fn record_query_keeps_each_search_once_newest_first() {
+        let mut history = History::default();
+        history.record_query("fire", "firefox.desktop");
+        history.record_query("  term ", "foot.desktop");
+        history.record_query("", "nothing.desktop");
+        history.record_query("fire", "firefox-esr.desktop");
+
+        assert_eq!(
+            queries(&history),
+            [("fire", "firefox-esr.desktop"), ("term", "foot.desktop")]
+        );
+    }
+
+    #[test]
+    fn record_query_drops_the_oldest_searches() {
+        let mut history = History::default();
+        for i in 0..MAX_QUERIES + 5 {
+            history.record_query(&format!("query {}", i), "app.desktop");
+        }
+
+        assert_eq!(history.queries.len(), MAX_QUERIES);
+        assert_eq!(history.queries[0].query, format!("query {}", MAX_QUERIES + 4));
+    }
+
+    #[test]
+    fn learned_matches_prefixes_case_insensitively() {
+        let mut history = History::default();
+        history.record_query("firefox", "firefox.desktop");
+        history.record_query("files", "nautilus.desktop");
+
+        assert_eq!(history.learned("FI"), Some("nautilus.desktop"));
+        assert_eq!(history.learned("fir"), Some("firefox.desktop"));
+        assert_eq!(history.learned("firefox nightly"), None);
+        assert_eq!(history.learned(""), None);
+    }
+
+    #[test]
+    fn learned_needs_two_characters_unless_exact() {
+        let mut history = History::default();
+        history.record_query("firefox", "firefox.desktop");
+        assert_eq!(history.learned("f"), None);
+
+        history.record_query("F", "foot.desktop");
+        assert_eq!(history.learned("f"), Some("foot.desktop"));
+        assert_eq!(history.learned("fi"), Some("firefox.desktop"));
+    }
+}
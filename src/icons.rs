@@ -2,6 +2,7 @@ use nerd_font_symbols::{fa, md, oct};
 
 pub fn category_icon(category: &str) -> &'static str {
     match category {
+        "Favorites" => fa::FA_STAR,
         "Recent" => fa::FA_CLOCK_ROTATE_LEFT,
         "Utilities" => fa::FA_GEAR,
         "Development" => fa::FA_HAMMER,
@@ -16,3 +17,6 @@ pub fn category_icon(category: &str) -> &'static str {
         _ => oct::OCT_DASH,
     }
 }
+
+/// Marker shown after pinned apps
+pub const PINNED: &str = fa::FA_THUMBTACK;
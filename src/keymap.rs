@@ -0,0 +1,330 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use eyre::{eyre, Result};
+
+/// Something a key can be bound to under `dstl.keys`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    First,
+    Last,
+    Launch,
+    ShowActions,
+    ToggleMode,
+    FocusNext,
+    FocusPrev,
+    /// Close the action menu or hidden view, otherwise quit
+    Back,
+    Quit,
+    ClearQuery,
+    TogglePin,
+    ToggleDetails,
+    ToggleHidden,
+    HiddenView,
+    /// Insert the system clipboard into the query
+    Paste,
+    /// Recall the previous / next search from the history
+    PrevQuery,
+    NextQuery,
+}
+
+impl Action {
+    const ALL: [(&'static str, Action); 23] = [
+        ("up", Action::Up),
+        ("down", Action::Down),
+        ("left", Action::Left),
+        ("right", Action::Right),
+        ("page_up", Action::PageUp),
+        ("page_down", Action::PageDown),
+        ("first", Action::First),
+        ("last", Action::Last),
+        ("launch", Action::Launch),
+        ("show_actions", Action::ShowActions),
+        ("toggle_mode", Action::ToggleMode),
+        ("focus_next", Action::FocusNext),
+        ("focus_prev", Action::FocusPrev),
+        ("back", Action::Back),
+        ("quit", Action::Quit),
+        ("clear_query", Action::ClearQuery),
+        ("toggle_pin", Action::TogglePin),
+        ("toggle_details", Action::ToggleDetails),
+        ("toggle_hidden", Action::ToggleHidden),
+        ("hidden_view", Action::HiddenView),
+        ("paste", Action::Paste),
+        ("prev_query", Action::PrevQuery),
+        ("next_query", Action::NextQuery),
+    ];
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Self::ALL
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|&(_, action)| action)
+    }
+}
+
+/// A key plus the modifiers held with it, e.g. `ctrl+n`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        // Shift is already in the case of a character, and Shift+Tab arrives as BackTab
+        let modifiers = match code {
+            KeyCode::Char(_) | KeyCode::BackTab => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        KeyChord { code, modifiers }
+    }
+
+    /// Parse `[ctrl+][alt+][shift+]<key>`; modifier and key names are case-insensitive,
+    /// single characters are not (`H` is Shift+h)
+    pub fn parse(chord: &str) -> Option<KeyChord> {
+        // "+" and "ctrl++" bind the plus key itself
+        let (prefix, key) = match chord.strip_suffix('+') {
+            Some("") => ("", "+"),
+            Some(rest) if rest.ends_with('+') => (&rest[..rest.len() - 1], "+"),
+            _ => chord.rsplit_once('+').unwrap_or(("", chord)),
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for part in prefix.split('+').filter(|_| !prefix.is_empty()) {
+            modifiers |= match part.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "space" => KeyCode::Char(' '),
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" | "page_up" => KeyCode::PageUp,
+                "pagedown" | "page_down" => KeyCode::PageDown,
+                name => {
+                    let n: u8 = name.strip_prefix('f')?.parse().ok()?;
+                    if !(1..=24).contains(&n) {
+                        return None;
+                    }
+                    KeyCode::F(n)
+                }
+            },
+        };
+
+        Some(KeyChord::new(code, modifiers))
+    }
+}
+
+impl From<KeyEvent> for KeyChord {
+    fn from(key: KeyEvent) -> Self {
+        KeyChord::new(key.code, key.modifiers)
+    }
+}
+
+/// Which action each key chord triggers
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(KeyChord, Action)>,
+}
+
+impl Keymap {
+    pub fn action(&self, key: KeyEvent) -> Option<Action> {
+        let chord = KeyChord::from(key);
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == chord)
+            .map(|&(_, action)| action)
+    }
+
+    /// Replace the chords of `action` with `chords`, taking them away from any other action
+    pub fn bind(&mut self, action: Action, chords: &[KeyChord]) {
+        self.bindings
+            .retain(|(chord, bound)| *bound != action && !chords.contains(chord));
+        self.bindings
+            .extend(chords.iter().map(|&chord| (chord, action)));
+    }
+
+    /// Apply `(action name, chords)` pairs read from `dstl.keys`
+    pub fn with_overrides(mut self, overrides: &[(String, Vec<String>)]) -> Result<Self> {
+        for (name, chords) in overrides {
+            let action = Action::from_name(name)
+                .ok_or_else(|| eyre!("Unknown action \"{}\" in dstl.keys", name))?;
+
+            let chords = chords
+                .iter()
+                .map(|chord| {
+                    KeyChord::parse(chord)
+                        .ok_or_else(|| eyre!("Can't parse key \"{}\" for dstl.keys.{}", chord, name))
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            self.bind(action, &chords);
+        }
+
+        Ok(self)
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let defaults = [
+            ("esc", Action::Back),
+            ("q", Action::Quit),
+            ("enter", Action::Launch),
+            ("alt+enter", Action::ShowActions),
+            ("tab", Action::FocusNext),
+            ("shift+tab", Action::FocusPrev),
+            ("m", Action::ToggleMode),
+            ("up", Action::Up),
+            ("k", Action::Up),
+            ("down", Action::Down),
+            ("j", Action::Down),
+            ("left", Action::Left),
+            ("h", Action::Left),
+            ("right", Action::Right),
+            ("l", Action::Right),
+            ("pageup", Action::PageUp),
+            ("pagedown", Action::PageDown),
+            ("home", Action::First),
+            ("end", Action::Last),
+            ("ctrl+l", Action::ClearQuery),
+            ("p", Action::TogglePin),
+            ("alt+p", Action::TogglePin),
+            ("i", Action::ToggleDetails),
+            ("alt+i", Action::ToggleDetails),
+            ("x", Action::ToggleHidden),
+            ("alt+x", Action::ToggleHidden),
+            ("H", Action::HiddenView),
+            ("alt+h", Action::HiddenView),
+            ("ctrl+v", Action::Paste),
+            ("shift+insert", Action::Paste),
+            ("ctrl+p", Action::PrevQuery),
+            ("alt+up", Action::PrevQuery),
+            ("ctrl+n", Action::NextQuery),
+            ("alt+down", Action::NextQuery),
+        ];
+
+        let bindings = defaults
+            .iter()
+            .map(|&(chord, action)| (KeyChord::parse(chord).expect("valid default key"), action))
+            .collect();
+
+        Keymap { bindings }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(code: KeyCode, modifiers: KeyModifiers) -> Option<KeyChord> {
+        Some(KeyChord { code, modifiers })
+    }
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn plus_key_parses_alone_and_with_modifiers() {
+        assert_eq!(KeyChord::parse("+"), chord(KeyCode::Char('+'), KeyModifiers::NONE));
+        assert_eq!(KeyChord::parse("ctrl++"), chord(KeyCode::Char('+'), KeyModifiers::CONTROL));
+        assert_eq!(
+            KeyChord::parse("ctrl+alt++"),
+            chord(KeyCode::Char('+'), KeyModifiers::CONTROL | KeyModifiers::ALT)
+        );
+        assert_eq!(KeyChord::parse("ctrl+"), None);
+    }
+
+    #[test]
+    fn shift_is_folded_into_characters_and_tab() {
+        assert_eq!(KeyChord::parse("shift+tab"), chord(KeyCode::BackTab, KeyModifiers::NONE));
+        assert_eq!(KeyChord::parse("shift+tab"), KeyChord::parse("backtab"));
+        assert_eq!(KeyChord::parse("H"), chord(KeyCode::Char('H'), KeyModifiers::NONE));
+        assert_eq!(KeyChord::parse("shift+h"), KeyChord::parse("H"));
+        assert_ne!(KeyChord::parse("h"), KeyChord::parse("H"));
+        // Terminals report Shift+h as 'H' with SHIFT held
+        assert_eq!(
+            KeyChord::from(key(KeyCode::Char('H'), KeyModifiers::SHIFT)),
+            KeyChord::parse("H").unwrap()
+        );
+    }
+
+    #[test]
+    fn named_and_function_keys() {
+        assert_eq!(KeyChord::parse("Ctrl+Enter"), chord(KeyCode::Enter, KeyModifiers::CONTROL));
+        assert_eq!(KeyChord::parse("alt+PageDown"), chord(KeyCode::PageDown, KeyModifiers::ALT));
+        assert_eq!(KeyChord::parse("f1"), chord(KeyCode::F(1), KeyModifiers::NONE));
+        assert_eq!(KeyChord::parse("shift+F24"), chord(KeyCode::F(24), KeyModifiers::SHIFT));
+        assert_eq!(KeyChord::parse("f0"), None);
+        assert_eq!(KeyChord::parse("f25"), None);
+    }
+
+    #[test]
+    fn bad_chords_are_rejected() {
+        assert_eq!(KeyChord::parse(""), None);
+        assert_eq!(KeyChord::parse("hyper+x"), None);
+        assert_eq!(KeyChord::parse("ctrl+nope"), None);
+    }
+
+    #[test]
+    fn overrides_reject_unknown_actions_and_keys() {
+        let unknown = Keymap::default().with_overrides(&[("explode".to_string(), vec!["x".to_string()])]);
+        assert!(unknown.unwrap_err().to_string().contains("Unknown action \"explode\""));
+
+        let bad_key = Keymap::default().with_overrides(&[("up".to_string(), vec!["ctrl+nope".to_string()])]);
+        assert!(bad_key.unwrap_err().to_string().contains("Can't parse key \"ctrl+nope\""));
+    }
+
+    #[test]
+    fn binding_a_chord_takes_it_from_its_old_action() {
+        let keymap = Keymap::default()
+            .with_overrides(&[("quit".to_string(), vec!["j".to_string()])])
+            .unwrap();
+        assert_eq!(keymap.action(key(KeyCode::Char('j'), KeyModifiers::NONE)), Some(Action::Quit));
+        // q was quit's only default key, and down keeps its arrow
+        assert_eq!(keymap.action(key(KeyCode::Char('q'), KeyModifiers::NONE)), None);
+        assert_eq!(keymap.action(key(KeyCode::Down, KeyModifiers::NONE)), Some(Action::Down));
+    }
+
+    #[test]
+    fn show_actions_defaults_to_a_key_terminals_send() {
+        // Without keyboard enhancement, Ctrl+Enter arrives as a plain Enter
+        let keymap = Keymap::default();
+        assert_eq!(keymap.action(key(KeyCode::Enter, KeyModifiers::ALT)), Some(Action::ShowActions));
+        assert_eq!(keymap.action(key(KeyCode::Enter, KeyModifiers::NONE)), Some(Action::Launch));
+    }
+
+    #[test]
+    fn clear_query_has_a_default_key() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.action(key(KeyCode::Char('l'), KeyModifiers::CONTROL)), Some(Action::ClearQuery));
+    }
+}
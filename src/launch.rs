@@ -1,23 +1,42 @@
-use std::process::{Command, Stdio};
+use std::ffi::CString;
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
 use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use crate::app::AppEntry;
 use crate::config::DstlConfig;
 
 pub fn launch_app(entry: &AppEntry, config: &DstlConfig) {
     let terminal = &config.terminal;
 
+    // Spawn the parsed argv directly; only entries without one are shell commands
+    let argv: Vec<&str> = if entry.argv.is_empty() {
+        vec!["sh", "-c", &entry.exec]
+    } else {
+        entry.argv.iter().map(String::as_str).collect()
+    };
+
     let mut cmd = if entry.terminal || entry.needs_terminal() {
         // Terminal app
         let mut c = Command::new(terminal);
-        c.arg("-e").arg(&entry.exec);
+        c.arg("-e").args(&argv);
         c
     } else {
         // GUI app
-        let mut c = Command::new("sh");
-        c.arg("-c").arg(&entry.exec);
+        let mut c = Command::new(argv[0]);
+        c.args(&argv[1..]);
         c
     };
 
+    // Path= working directory and `env FOO=bar` assignments from the entry
+    if let Some(ref dir) = entry.working_dir
+        && dir.is_dir()
+    {
+        cmd.current_dir(dir);
+    }
+    cmd.envs(entry.env.iter().map(|(k, v)| (k, v)));
+
     // Fully detach (don't block, don't get killed with parent)
     unsafe {
         cmd.pre_exec(|| {
@@ -32,3 +51,36 @@ pub fn launch_app(entry: &AppEntry, config: &DstlConfig) {
         .stderr(Stdio::null())
         .spawn();
 }
+
+/// Check that a path is a regular file (after following symlinks) we may execute
+pub fn is_executable(path: &Path) -> bool {
+    // metadata() follows symlinks, so dangling links fail here
+    let Ok(meta) = fs::metadata(path) else {
+        return false;
+    };
+    if !meta.is_file() {
+        return false;
+    }
+
+    let Ok(c_path) = CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // access() checks the X bits against our own user and groups, which the mode bits alone don't.
+    // SAFETY: c_path is a valid NUL-terminated string that outlives the call, and access()
+    // only reads it.
+    unsafe { libc::access(c_path.as_ptr(), libc::X_OK) == 0 }
+}
+
+/// Resolve a program the way the shell would: absolute paths as-is, bare names via $PATH
+pub fn find_program(program: &str) -> Option<PathBuf> {
+    if program.contains('/') {
+        let path = PathBuf::from(program);
+        return is_executable(&path).then_some(path);
+    }
+
+    let path_var = std::env::var_os("PATH")?;
+    std::env::split_paths(&path_var)
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .map(|dir| dir.join(program))
+        .find(|path| is_executable(path))
+}
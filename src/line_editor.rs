@@ -0,0 +1,472 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// A single-line text field with readline-style editing, a yank buffer and undo/redo
+///
+/// The cursor counts grapheme clusters (what the user sees as one character), so
+/// emoji, CJK and combining marks move and delete as a whole.
+#[derive(Debug, Clone, Default)]
+pub struct LineEditor {
+    text: String,
+    cursor: usize,
+    /// Text removed by the last kill, for `yank`
+    killed: String,
+    undo: Vec<(String, usize)>,
+    redo: Vec<(String, usize)>,
+    /// Whether the last edit was typing, so a typed word undoes in one step
+    typing: bool,
+}
+
+impl LineEditor {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    fn graphemes(&self) -> Vec<&str> {
+        self.text.graphemes(true).collect()
+    }
+
+    fn len(&self) -> usize {
+        self.text.graphemes(true).count()
+    }
+
+    /// Byte offset of the grapheme at `pos`
+    fn byte_at(&self, pos: usize) -> usize {
+        self.text
+            .grapheme_indices(true)
+            .nth(pos)
+            .map_or(self.text.len(), |(i, _)| i)
+    }
+
+    /// Grapheme index of the boundary at or after byte offset `byte`
+    fn grapheme_at_byte(&self, byte: usize) -> usize {
+        self.text
+            .grapheme_indices(true)
+            .take_while(|(i, _)| *i < byte)
+            .count()
+    }
+
+    /// Insert at the cursor, which ends up after the inserted text; a combining
+    /// mark merges with the grapheme before it
+    fn insert_at_cursor(&mut self, s: &str) {
+        let at = self.byte_at(self.cursor);
+        self.text.insert_str(at, s);
+        self.cursor = self.grapheme_at_byte(at + s.len());
+    }
+
+    pub fn set_cursor(&mut self, pos: usize) {
+        self.cursor = pos.min(self.len());
+        self.typing = false;
+    }
+
+    /// Remember the current state before an edit
+    fn checkpoint(&mut self) {
+        self.undo.push((self.text.clone(), self.cursor));
+        self.redo.clear();
+        self.typing = false;
+    }
+
+    /// Replace the whole text, e.g. with a recalled query; undoable
+    pub fn set_text(&mut self, text: &str) {
+        if text != self.text {
+            self.checkpoint();
+            self.text = text.to_string();
+        }
+        self.cursor = self.len();
+    }
+
+    pub fn clear(&mut self) {
+        self.set_text("");
+    }
+
+    pub fn insert(&mut self, c: char) {
+        if !self.typing {
+            self.checkpoint();
+            self.typing = true;
+        }
+        self.insert_at_cursor(c.encode_utf8(&mut [0; 4]));
+    }
+
+    pub fn insert_str(&mut self, s: &str) {
+        if s.is_empty() {
+            return;
+        }
+        self.checkpoint();
+        self.insert_at_cursor(s);
+    }
+
+    /// Remove the graphemes in `start..end`, returning them
+    fn remove(&mut self, start: usize, end: usize) -> String {
+        if start >= end {
+            return String::new();
+        }
+        self.checkpoint();
+        let (from, to) = (self.byte_at(start), self.byte_at(end));
+        let removed = self.text[from..to].to_string();
+        self.text.replace_range(from..to, "");
+        self.cursor = start;
+        removed
+    }
+
+    /// Remove `start..end` into the yank buffer
+    fn kill(&mut self, start: usize, end: usize) {
+        let killed = self.remove(start, end);
+        if !killed.is_empty() {
+            self.killed = killed;
+        }
+    }
+
+    pub fn delete_back(&mut self) {
+        if self.cursor > 0 {
+            self.remove(self.cursor - 1, self.cursor);
+        }
+    }
+
+    pub fn delete_forward(&mut self) {
+        self.remove(self.cursor, (self.cursor + 1).min(self.len()));
+    }
+
+    /// Ctrl+W: kill back to the previous whitespace
+    pub fn kill_word_back(&mut self) {
+        let graphemes = self.graphemes();
+        let mut start = self.cursor;
+        while start > 0 && is_space(graphemes[start - 1]) {
+            start -= 1;
+        }
+        while start > 0 && !is_space(graphemes[start - 1]) {
+            start -= 1;
+        }
+        self.kill(start, self.cursor);
+    }
+
+    /// Alt+D: kill to the end of the next word
+    pub fn kill_word_forward(&mut self) {
+        let end = self.word_end();
+        self.kill(self.cursor, end);
+    }
+
+    pub fn kill_to_start(&mut self) {
+        self.kill(0, self.cursor);
+    }
+
+    pub fn kill_to_end(&mut self) {
+        self.kill(self.cursor, self.len());
+    }
+
+    /// Insert the last killed text at the cursor
+    pub fn yank(&mut self) {
+        let killed = self.killed.clone();
+        self.insert_str(&killed);
+    }
+
+    pub fn move_left(&mut self) {
+        self.set_cursor(self.cursor.saturating_sub(1));
+    }
+
+    pub fn move_right(&mut self) {
+        self.set_cursor(self.cursor + 1);
+    }
+
+    pub fn move_home(&mut self) {
+        self.set_cursor(0);
+    }
+
+    pub fn move_end(&mut self) {
+        self.set_cursor(self.len());
+    }
+
+    /// Start of the word before the cursor; words are runs of letters and digits
+    fn word_start(&self) -> usize {
+        let graphemes = self.graphemes();
+        let mut pos = self.cursor.min(graphemes.len());
+        while pos > 0 && !is_word(graphemes[pos - 1]) {
+            pos -= 1;
+        }
+        while pos > 0 && is_word(graphemes[pos - 1]) {
+            pos -= 1;
+        }
+        pos
+    }
+
+    /// End of the word after the cursor
+    fn word_end(&self) -> usize {
+        let graphemes = self.graphemes();
+        let mut pos = self.cursor.min(graphemes.len());
+        while pos < graphemes.len() && !is_word(graphemes[pos]) {
+            pos += 1;
+        }
+        while pos < graphemes.len() && is_word(graphemes[pos]) {
+            pos += 1;
+        }
+        pos
+    }
+
+    pub fn word_left(&mut self) {
+        self.set_cursor(self.word_start());
+    }
+
+    pub fn word_right(&mut self) {
+        self.set_cursor(self.word_end());
+    }
+
+    pub fn undo(&mut self) {
+        if let Some((text, cursor)) = self.undo.pop() {
+            let current = std::mem::replace(&mut self.text, text);
+            self.redo.push((current, self.cursor));
+            self.cursor = cursor;
+            self.typing = false;
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some((text, cursor)) = self.redo.pop() {
+            let current = std::mem::replace(&mut self.text, text);
+            self.undo.push((current, self.cursor));
+            self.cursor = cursor;
+            self.typing = false;
+        }
+    }
+
+    /// The part of the text that fits in `width` terminal columns with the cursor in view
+    pub fn view(&self, width: usize) -> View<'_> {
+        if width == 0 {
+            return View { text: "", first: self.cursor, cursor_x: 0 };
+        }
+
+        let graphemes = self.graphemes();
+        let cursor_col: usize = graphemes[..self.cursor].iter().map(|g| g.width()).sum();
+
+        // Keep one column free for the cursor at the end of the text
+        let overflow = (cursor_col + 1).saturating_sub(width);
+        let (mut first, mut skipped) = (0, 0);
+        while skipped < overflow && first < graphemes.len() {
+            skipped += graphemes[first].width();
+            first += 1;
+        }
+
+        let start = self.byte_at(first);
+        let mut end = start;
+        let mut used = 0;
+        for g in &graphemes[first..] {
+            used += g.width();
+            if used > width {
+                break;
+            }
+            end += g.len();
+        }
+
+        View {
+            text: &self.text[start..end],
+            first,
+            cursor_x: cursor_col - skipped,
+        }
+    }
+
+    /// Move the cursor to the grapheme drawn at column `x` of `view(width)`
+    pub fn click(&mut self, width: usize, x: usize) {
+        let view = self.view(width);
+        let mut col = 0;
+        let mut pos = view.first;
+        for g in view.text.graphemes(true) {
+            let w = g.width();
+            // Past the middle of a wide character puts the cursor after it
+            if col + w.div_ceil(2) > x {
+                break;
+            }
+            col += w;
+            pos += 1;
+        }
+        self.set_cursor(pos);
+    }
+}
+
+/// What `LineEditor::view` shows of the text
+pub struct View<'a> {
+    pub text: &'a str,
+    /// Grapheme index of the first visible grapheme
+    first: usize,
+    /// Terminal column of the cursor, counted from the start of `text`
+    pub cursor_x: usize,
+}
+
+fn is_space(grapheme: &str) -> bool {
+    grapheme.chars().all(char::is_whitespace)
+}
+
+fn is_word(grapheme: &str) -> bool {
+    grapheme.chars().next().is_some_and(char::is_alphanumeric)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_text(text: &str) -> LineEditor {
+        let mut editor = LineEditor::default();
+        editor.set_text(text);
+        editor
+    }
+
+    fn typed(text: &str) -> LineEditor {
+        let mut editor = LineEditor::default();
+        text.chars().for_each(|c| editor.insert(c));
+        editor
+    }
+
+    #[test]
+    fn undo_takes_back_a_typed_word_at_once() {
+        let mut editor = typed("fire");
+        editor.insert(' ');
+        editor.move_left();
+        editor.move_right();
+        editor.insert_str("fox");
+
+        editor.undo();
+        assert_eq!(editor.text(), "fire ");
+        editor.undo();
+        assert_eq!(editor.text(), "");
+        editor.undo();
+        assert_eq!(editor.text(), "");
+    }
+
+    #[test]
+    fn undo_restores_killed_text_and_cursor() {
+        let mut editor = with_text("open files now");
+        editor.set_cursor(10);
+        editor.kill_word_back();
+        assert_eq!(editor.text(), "open  now");
+
+        editor.undo();
+        assert_eq!(editor.text(), "open files now");
+        assert_eq!(editor.cursor, 10);
+    }
+
+    #[test]
+    fn undo_removes_a_yank() {
+        let mut editor = with_text("web browser");
+        editor.kill_word_back();
+        editor.move_home();
+        editor.yank();
+        assert_eq!(editor.text(), "browserweb ");
+
+        editor.undo();
+        assert_eq!(editor.text(), "web ");
+        assert_eq!(editor.cursor, 0);
+        editor.redo();
+        assert_eq!(editor.text(), "browserweb ");
+    }
+
+    #[test]
+    fn a_new_edit_clears_redo() {
+        let mut editor = typed("term");
+        editor.delete_back();
+        editor.undo();
+        assert_eq!(editor.text(), "term");
+
+        editor.insert('s');
+        editor.redo();
+        assert_eq!(editor.text(), "terms");
+    }
+
+    #[test]
+    fn combining_marks_and_zwj_sequences_edit_as_one_grapheme() {
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let mut editor = typed(&format!("e\u{301}{family}"));
+        assert_eq!(editor.len(), 2);
+        assert_eq!(editor.cursor, 2);
+
+        editor.delete_back();
+        assert_eq!(editor.text(), "e\u{301}");
+        editor.delete_back();
+        assert!(editor.is_empty());
+    }
+
+    #[test]
+    fn cjk_inserts_and_deletes_whole_characters() {
+        let mut editor = with_text("漢字");
+        editor.move_left();
+        editor.insert('a');
+        assert_eq!(editor.text(), "漢a字");
+        assert_eq!(editor.cursor, 2);
+
+        editor.move_home();
+        editor.delete_forward();
+        assert_eq!(editor.text(), "a字");
+        editor.move_end();
+        editor.delete_forward();
+        assert_eq!(editor.text(), "a字");
+        editor.delete_back();
+        assert_eq!(editor.text(), "a");
+    }
+
+    #[test]
+    fn view_scrolls_to_keep_the_cursor_visible() {
+        let mut editor = with_text("abcdef");
+        let view = editor.view(4);
+        assert_eq!((view.text, view.first, view.cursor_x), ("def", 3, 3));
+
+        editor.move_home();
+        let view = editor.view(4);
+        assert_eq!((view.text, view.first, view.cursor_x), ("abcd", 0, 0));
+    }
+
+    #[test]
+    fn wide_grapheme_at_the_right_edge() {
+        // 漢 would straddle the edge, so it's left out rather than cut in half
+        let mut editor = with_text("abcd漢");
+        editor.move_left();
+        let view = editor.view(5);
+        assert_eq!((view.text, view.cursor_x), ("abcd", 4));
+
+        // With the cursor after it, whole graphemes scroll off until it fits
+        editor.move_end();
+        let view = editor.view(5);
+        assert_eq!((view.text, view.first, view.cursor_x), ("cd漢", 2, 4));
+
+        let editor = with_text("漢字漢");
+        let view = editor.view(4);
+        assert_eq!((view.text, view.first, view.cursor_x), ("漢", 2, 2));
+    }
+
+    #[test]
+    fn zero_width_view_is_empty() {
+        let mut editor = with_text("漢字");
+        let view = editor.view(0);
+        assert_eq!((view.text, view.cursor_x), ("", 0));
+
+        editor.click(0, 3);
+        assert_eq!(editor.cursor, 2);
+    }
+
+    #[test]
+    fn click_on_either_half_of_a_wide_character() {
+        let mut editor = with_text("a漢b");
+        for (x, cursor) in [(0, 0), (1, 1), (2, 2), (3, 2), (4, 3), (9, 3)] {
+            editor.click(10, x);
+            assert_eq!(editor.cursor, cursor, "click at column {x}");
+        }
+    }
+
+    #[test]
+    fn word_motion_and_kill_across_mixed_width_text() {
+        let mut editor = with_text("foo 漢字-bar");
+        editor.word_left();
+        assert_eq!(editor.cursor, 7);
+        editor.word_left();
+        assert_eq!(editor.cursor, 4);
+        editor.word_right();
+        assert_eq!(editor.cursor, 6);
+
+        let mut editor = with_text("open 漢字テスト  ");
+        editor.kill_word_back();
+        assert_eq!(editor.text(), "open ");
+        editor.move_home();
+        editor.yank();
+        assert_eq!(editor.text(), "漢字テスト  open ");
+        assert_eq!(editor.cursor, 7);
+    }
+}
@@ -1,8 +1,14 @@
 mod app;
+mod cli;
+mod clipboard;
 mod config;
+mod desktop;
 mod events;
+mod history;
 mod icons;
+mod keymap;
 mod launch;
+mod line_editor;
 mod ui;
 
 use ratatui::{
@@ -10,12 +16,16 @@ use ratatui::{
     Terminal,
 };
 use std::{
-    io::{self, Write},
+    fs::OpenOptions,
+    io::{self, IsTerminal, Write},
     time::{Duration, Instant},
 };
 use crossterm::{
     cursor::{MoveTo, SetCursorStyle},
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event},
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, MouseButton, MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
@@ -23,36 +33,52 @@ use crossterm::{
 use eyre::Result;
 
 use app::{App, Focus, Mode, SinglePaneMode};
+use cli::Cli;
 use config::{load_launcher_config, CursorShape, SearchPosition};
 
 fn main() -> Result<()> {
     color_eyre::install()?;
 
-    let cfg = load_launcher_config();
+    let cli = Cli::parse();
 
-    let single_pane_mode = if cfg.dmenu {
+    let mut cfg = load_launcher_config(cli.config.as_deref());
+    cli.apply(&mut cfg);
+
+    // Piped stdin means we're being used as a dmenu replacement
+    let dmenu = cli.dmenu || !io::stdin().is_terminal();
+
+    let single_pane_mode = if dmenu {
         SinglePaneMode::Dmenu
+    } else if cfg.dmenu {
+        SinglePaneMode::Run
     } else {
         SinglePaneMode::DesktopApps
     };
 
     let start_mode = match cfg.start_mode {
+        _ if dmenu => Mode::SinglePane,
         config::StartMode::Dual => Mode::DualPane,
         config::StartMode::Single => Mode::SinglePane,
     };
 
+    // Read candidates before the terminal is switched to raw mode
+    let mut app = App::new(single_pane_mode, start_mode, &cfg);
+
     enable_raw_mode()?;
-    let mut stdout = io::stdout();
+    let mut output = tui_output()?;
     
     // Set cursor color using ANSI escape codes
-    set_cursor_color(&mut stdout, &cfg.colors.cursor_color)?;
+    set_cursor_color(&mut output, &cfg.colors.cursor_color)?;
     
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
+    // Pastes arrive as one event instead of keystrokes (whose newlines would press Enter)
+    execute!(output, EnterAlternateScreen, EnableBracketedPaste)?;
+    // Without capture the terminal keeps its own text selection
+    if cfg.mouse {
+        execute!(output, EnableMouseCapture)?;
+    }
+    let backend = CrosstermBackend::new(output);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = App::new(single_pane_mode, start_mode, &cfg);
-
     warmup_icons(&mut terminal, &app, &cfg)?;
 
     if start_mode == Mode::DualPane && !app.categories.is_empty() {
@@ -65,11 +91,10 @@ fn main() -> Result<()> {
     let res = run_app(&mut terminal, &mut app, &cfg);
 
     disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableBracketedPaste)?;
+    if cfg.mouse {
+        execute!(terminal.backend_mut(), DisableMouseCapture)?;
+    }
     terminal.show_cursor()?;
     
     // Reset cursor color to default
@@ -79,18 +104,40 @@ fn main() -> Result<()> {
         eprintln!("Error: {err:?}");
     }
 
-    if let Some(ref cmd) = app.app_to_launch {
-        if let Some(entry) = app.apps.iter().find(|a| &a.exec == cmd).cloned() {
-            app.add_to_recent(entry.name.clone());
-            crate::launch::launch_app(&entry, &app.config);
-        } else {
-            let _ = std::process::Command::new("sh").arg("-c").arg(cmd).spawn();
+    let Some(entry) = app.app_to_launch.take() else {
+        // Nothing picked: let dmenu pipelines know
+        if app.single_pane_mode == SinglePaneMode::Dmenu || cli.print_only {
+            std::process::exit(1);
         }
+        return Ok(());
+    };
+
+    let target = match app.action_to_launch {
+        Some(ref action) => entry.with_action(action),
+        None => entry.clone(),
+    };
+
+    // dmenu mode / --print-only: hand the selection to the pipeline instead of launching it
+    if app.single_pane_mode == SinglePaneMode::Dmenu || cli.print_only {
+        println!("{}", target.exec);
+        return Ok(());
     }
 
+    app.add_to_recent(&entry.id);
+    crate::launch::launch_app(&target, &app.config);
+
     Ok(())
 }
 
+/// Pick where the TUI is drawn: stdout normally, /dev/tty when stdout is piped
+fn tui_output() -> io::Result<Box<dyn Write>> {
+    if io::stdout().is_terminal() {
+        Ok(Box::new(io::stdout()))
+    } else {
+        Ok(Box::new(OpenOptions::new().write(true).open("/dev/tty")?))
+    }
+}
+
 /// Set the cursor color using ANSI escape codes
 fn set_cursor_color<W: Write>(writer: &mut W, color_hex: &str) -> Result<()> {
     if let Some((r, g, b)) = parse_hex_color(color_hex) {
@@ -171,19 +218,13 @@ fn run_app<B: Backend + ExecutableCommand>(
                 ),
             };
 
-            // Calculate scroll offset to match the Paragraph widget
+            // Same scrolling as the search bar: by display columns, not chars
             let padding_left = 1;
 
             let available_width = search_area.width.saturating_sub(2 + 2) as usize; 
             // borders + 2 padding
 
-            let horizontal_offset = if app.cursor_position >= available_width {
-                app.cursor_position - available_width + 1
-            } else {
-                0
-            };
-
-            let visible_cursor_pos = app.cursor_position - horizontal_offset;
+            let visible_cursor_pos = app.query.view(available_width).cursor_x;
 
             // cursor_x = left border + padding_left + local_pos
             let cursor_x = search_area.x + 1 + padding_left + visible_cursor_pos as u16;
@@ -234,11 +275,22 @@ fn run_app<B: Backend + ExecutableCommand>(
         }
 
         if event::poll(tick)? {
-            if let Event::Key(key) = event::read()? {
-                last_input = Instant::now();
-                if events::handle_key(app, key)? {
-                    break;
+            let done = match event::read()? {
+                Event::Key(key) => events::handle_key(app, key)?,
+                // The pointer passing over the terminal isn't input; only clicks and the wheel are
+                Event::Mouse(mouse) if matches!(
+                    mouse.kind,
+                    MouseEventKind::Down(MouseButton::Left) | MouseEventKind::ScrollUp | MouseEventKind::ScrollDown
+                ) => events::handle_mouse(app, mouse)?,
+                Event::Paste(text) => {
+                    events::handle_paste(app, &text);
+                    false
                 }
+                _ => continue,
+            };
+            last_input = Instant::now();
+            if done {
+                break;
             }
         }
     }
@@ -1,49 +1,28 @@
-use crate::app::{App, AppEntry, Focus};
+use crate::app::{App, Focus};
 use crate::ui::layout;
 use crate::config::{DstlConfig, SearchPosition};
 use ratatui::Frame;
+use ratatui::text::Line;
 
 pub fn draw(f: &mut Frame, app: &mut App, search_position: SearchPosition, config: &DstlConfig) {
     let (search_area, content_area) = layout::vertical_split(f, 3, search_position);
     
-    // Pass cursor_position to render_search_bar
     layout::render_search_bar(
         f,
         search_area,
-        &app.search_query,
-        app.cursor_position,
+        &app.query,
         app.focus,
+        app.vim_mode,
         config,
     );
     
+    let (content_area, details_area) = layout::details_split(content_area, app, config);
     let (categories_area, apps_area) = layout::horizontal_split(content_area);
-    let query_lower = app.search_query.to_lowercase();
-    
-    let (categories_to_show, category_indices): (Vec<String>, Vec<usize>) = if !query_lower.is_empty() {
-        app.categories
-            .iter()
-            .enumerate()
-            .filter(|(_, cat_name)| {
-                if *cat_name == "Recent" {
-                    app.recent_apps.iter().any(|recent_name| {
-                        app.apps.iter()
-                            .find(|a| &a.name == recent_name)
-                            .and_then(|a| app.matches_search(&a.name, &query_lower))
-                            .is_some()
-                    })
-                } else {
-                    app.apps.iter().any(|a| {
-                        &a.category == *cat_name && app.matches_search(&a.name, &query_lower).is_some()
-                    })
-                }
-            })
-            .map(|(idx, cat)| (cat.clone(), idx))
-            .unzip()
-    } else {
-        let cats: Vec<String> = app.categories.clone();
-        let indices: Vec<usize> = (0..app.categories.len()).collect();
-        (cats, indices)
-    };
+    let category_indices = app.matching_category_indices();
+    let categories_to_show: Vec<String> = category_indices
+        .iter()
+        .map(|&idx| app.categories[idx].clone())
+        .collect();
     
     let display_idx = category_indices.iter()
         .position(|&idx| idx == app.selected_category)
@@ -55,39 +34,37 @@ pub fn draw(f: &mut Frame, app: &mut App, search_position: SearchPosition, confi
         .cloned()
         .unwrap_or_default();
     
-    let mut apps_to_show: Vec<(AppEntry, i64)> = if selected_category_name == "Recent" {
-        app.recent_apps
-            .iter()
-            .filter_map(|recent_name| {
-                app.apps.iter()
-                    .find(|a| &a.name == recent_name)
-                    .cloned()
-            })
-            .filter_map(|a| app.matches_search(&a.name, &query_lower).map(|score| (a, score)))
-            .collect()
-    } else {
-        app.apps
-            .iter()
-            .filter(|a| a.category == selected_category_name)
-            .filter_map(|a| app.matches_search(&a.name, &query_lower).map(|score| (a.clone(), score)))
-            .collect()
-    };
-    
-    apps_to_show.sort_by(|a, b| b.1.cmp(&a.1));
-    let apps_to_show: Vec<AppEntry> = apps_to_show.into_iter().map(|(a, _)| a).collect();
+    let query = app.search_query();
+    let apps_to_show: Vec<Line> = app
+        .visible_apps_in_category(&selected_category_name)
+        .into_iter()
+        .map(|a| layout::app_label(app, a, &query, config))
+        .collect();
     
     if !apps_to_show.is_empty() && app.selected_app >= apps_to_show.len() {
         app.selected_app = apps_to_show.len() - 1;
     }
+
+    if let Some(area) = details_area {
+        layout::render_details(f, area, app, config);
+    }
     
-    let category_names: Vec<String> = categories_to_show
+    // Apps in several categories count towards each of them
+    let counts = app.category_counts();
+    let category_names: Vec<Line> = category_indices
         .iter()
-        .map(|c| format!("{}  {}", crate::icons::category_icon(c), c))
+        .zip(&categories_to_show)
+        .map(|(&idx, c)| {
+            let icon = config.categories.icon(c).unwrap_or_else(|| crate::icons::category_icon(c));
+            let count = counts.get(idx).copied().unwrap_or(0);
+            Line::from(format!("{}  {} ({})", icon, c, count))
+        })
         .collect();
     
     let categories_title = " Categories ";
     
-    layout::render_list(
+    app.areas.search = search_area;
+    app.areas.categories = Some(layout::render_list(
         f,
         categories_area,
         categories_title,
@@ -95,15 +72,19 @@ pub fn draw(f: &mut Frame, app: &mut App, search_position: SearchPosition, confi
         display_idx,
         app.focus == Focus::Categories,
         config,
-    );
+    ));
     
-    let app_names: Vec<String> = apps_to_show.iter().map(|a| a.name.clone()).collect();
+    if let Some(ref entry) = app.action_menu {
+        app.areas.apps = layout::render_action_menu(f, apps_area, entry, app.selected_action, config);
+        return;
+    }
+
     let selected_index_in_apps = if apps_to_show.is_empty() { 0 } else { app.selected_app };
-    layout::render_list(
+    app.areas.apps = layout::render_list(
         f,
         apps_area,
-        " Apps ",
-        &app_names,
+        layout::apps_title(app),
+        &apps_to_show,
         selected_index_in_apps,
         app.focus == Focus::Apps,
         config,
@@ -1,11 +1,14 @@
 use ratatui::{
     Frame,
     layout::{Layout, Constraint, Direction, Rect},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
-    style::{Style, Color},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    style::{Style, Color, Modifier},
 };
-use crate::app::Focus;
-use crate::config::{DstlConfig, LauncherTheme, SearchPosition};
+use crate::app::{App, AppEntry, Focus, ListArea, SearchQuery, VimMode};
+use crate::line_editor::LineEditor;
+use crate::config::{DetailsPosition, DstlConfig, LauncherTheme, SearchPosition};
+use crate::history;
 
 pub fn vertical_split(f: &Frame, search_height: u16, search_position: SearchPosition) -> (Rect, Rect) {
     let full_area = f.area();
@@ -35,12 +38,36 @@ pub fn horizontal_split(area: Rect) -> (Rect, Rect) {
     (chunks[0], chunks[1])
 }
 
+/// Carve the detail pane out of `area` when it's open: (rest, details)
+pub fn details_split(area: Rect, app: &App, config: &DstlConfig) -> (Rect, Option<Rect>) {
+    if !app.show_details {
+        return (area, None);
+    }
+
+    match config.details_position {
+        DetailsPosition::Bottom => {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(3), Constraint::Length(config.details_size)])
+                .split(area);
+            (chunks[0], Some(chunks[1]))
+        }
+        DetailsPosition::Right => {
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Min(20), Constraint::Percentage(config.details_size.min(100))])
+                .split(area);
+            (chunks[0], Some(chunks[1]))
+        }
+    }
+}
+
 pub fn render_search_bar(
     f: &mut Frame,
     area: Rect,
-    query: &str,
-    cursor_position: usize,
+    query: &LineEditor,
     focus: Focus,
+    vim_mode: Option<VimMode>,
     config: &DstlConfig,
 ) {
     let border_color = if focus == Focus::Search {
@@ -50,7 +77,10 @@ pub fn render_search_bar(
     };
 
     let block = Block::default()
-        .title(" Search ")
+        .title(match vim_mode {
+            Some(mode) => format!(" {} [{}] ", config.prompt, mode.label()),
+            None => format!(" {} ", config.prompt),
+        })
         .borders(Borders::ALL)
         .border_type(LauncherTheme::parse_border_type(&config.colors.border_style))
         .border_style(Style::default().fg(border_color));
@@ -58,26 +88,15 @@ pub fn render_search_bar(
     // Inner area after borders
     let inner = block.inner(area);
     
-    let query_chars: Vec<char> = query.chars().collect();
-    let query_len = query_chars.len();
-    
     // Add padding (1 space on each side)
     let padding = 1;
     let available_width = (inner.width as usize).saturating_sub(padding * 2);
     
-    // Calculate scrolling offset to keep cursor visible
-    let scroll_offset = if cursor_position >= available_width {
-        cursor_position - available_width + 1
-    } else {
-        0
-    };
-    
-    let visible_start = scroll_offset;
-    let visible_end = (visible_start + available_width).min(query_len);
-    let visible_text: String = query_chars[visible_start..visible_end].iter().collect();
+    // Scroll by display columns to keep cursor visible
+    let view = query.view(available_width);
     
     // Add padding spaces to the displayed text
-    let padded_text = format!(" {} ", visible_text);
+    let padded_text = format!(" {} ", view.text);
     
     let paragraph = Paragraph::new(padded_text)
         .block(block)
@@ -87,22 +106,51 @@ pub fn render_search_bar(
     
     // Set cursor position if search is focused (account for padding)
     if focus == Focus::Search {
-        let cursor_x = inner.x + padding as u16 + (cursor_position - scroll_offset) as u16;
+        let cursor_x = inner.x + padding as u16 + view.cursor_x as u16;
         let cursor_y = inner.y;
         f.set_cursor_position((cursor_x, cursor_y));
     }
 }
 
 
+/// Title of the app list, which also doubles as the unhide view
+pub fn apps_title(app: &App) -> &'static str {
+    if app.show_hidden { " Hidden Apps " } else { " Apps " }
+}
+
+/// An app's list label: its glyph, if any, then the name with the characters
+/// matching the search highlighted, and a pin for favorites
+pub fn app_label(app: &App, entry: &AppEntry, query: &SearchQuery, config: &DstlConfig) -> Line<'static> {
+    let mut spans = Vec::new();
+    if let Some(ref glyph) = entry.glyph {
+        spans.push(Span::raw(format!("{}  ", glyph)));
+    }
+
+    let matched = app.match_positions(entry, query);
+    let match_style = config.colors.match_style();
+    for (i, c) in entry.name.chars().enumerate() {
+        if matched.contains(&i) {
+            spans.push(Span::styled(c.to_string(), match_style));
+        } else {
+            spans.push(Span::raw(c.to_string()));
+        }
+    }
+
+    if app.history.is_pinned(&entry.id) {
+        spans.push(Span::raw(format!(" {}", crate::icons::PINNED)));
+    }
+    Line::from(spans)
+}
+
 pub fn render_list(
     f: &mut Frame,
     area: Rect,
     title: &str,
-    items: &[String],
+    items: &[Line],
     selected: usize,
     focus_on_title: bool,
     config: &DstlConfig,
-) {
+) -> ListArea {
     let mut state = ListState::default();
     let sel = if selected >= items.len() { 0 } else { selected };
     state.select(Some(sel));
@@ -120,13 +168,19 @@ pub fn render_list(
         .border_style(Style::default().fg(border_color));
     
     let list_items: Vec<ListItem> = items.iter()
-        .map(|a| ListItem::new(format!(" {} ", a)))
+        .map(|line| {
+            // Pad each row by a space on both sides
+            let mut spans = vec![Span::raw(" ")];
+            spans.extend(line.spans.iter().cloned());
+            spans.push(Span::raw(" "));
+            ListItem::new(Line::from(spans))
+        })
         .collect();
     
     let highlight_color = LauncherTheme::parse_color(&config.colors.highlight);
     let highlight_style = match config.colors.highlight_type.to_lowercase().as_str() {
         "foreground" => Style::default().fg(highlight_color),
-        "background" | _ => Style::default().bg(highlight_color).fg(Color::Black),
+        _ => Style::default().bg(highlight_color).fg(Color::Black),
     };
     
     let list = List::new(list_items)
@@ -135,4 +189,92 @@ pub fn render_list(
         .highlight_symbol("");
     
     f.render_stateful_widget(list, area, &mut state);
+
+    ListArea { area, offset: state.offset() }
+}
+
+/// List an app's desktop actions in place of the app list
+pub fn render_action_menu(
+    f: &mut Frame,
+    area: Rect,
+    entry: &AppEntry,
+    selected: usize,
+    config: &DstlConfig,
+) -> ListArea {
+    let title = format!(" {} ", entry.name);
+    let action_names: Vec<Line> = entry.actions.iter().map(|a| Line::from(a.name.clone())).collect();
+    render_list(f, area, &title, &action_names, selected, true, config)
+}
+
+/// Show everything we know about the selected entry
+pub fn render_details(f: &mut Frame, area: Rect, app: &App, config: &DstlConfig) {
+    let border_color = LauncherTheme::parse_color(&config.colors.border);
+    let label_style = Style::default()
+        .fg(LauncherTheme::parse_color(&config.colors.details_label))
+        .add_modifier(Modifier::BOLD);
+
+    let entry = app.action_menu.as_ref().or_else(|| app.selected_entry());
+    let title = entry.map_or(" Details ".to_string(), |e| format!(" {} ", e.name));
+
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_type(LauncherTheme::parse_border_type(&config.colors.border_style))
+        .border_style(Style::default().fg(border_color));
+
+    let mut fields: Vec<(&str, String)> = Vec::new();
+    if let Some(entry) = entry {
+        if let Some(ref generic_name) = entry.generic_name {
+            fields.push(("Generic name", generic_name.clone()));
+        }
+        if let Some(ref comment) = entry.comment {
+            fields.push(("Comment", comment.clone()));
+        }
+        fields.push(("Exec", entry.exec.clone()));
+        if let Some(ref path) = entry.desktop_file {
+            fields.push(("File", path.display().to_string()));
+        }
+        let categories = if entry.categories.is_empty() { &entry.groups } else { &entry.categories };
+        if !categories.is_empty() {
+            fields.push(("Categories", categories.join(", ")));
+        }
+        let terminal = entry.terminal || entry.needs_terminal();
+        fields.push(("Terminal", if terminal { "yes" } else { "no" }.to_string()));
+
+        let usage = app.history.get(&entry.id);
+        fields.push(("Launches", usage.map_or(0, |u| u.count).to_string()));
+        fields.push((
+            "Last used",
+            usage.map_or("never".to_string(), |u| format_age(history::now().saturating_sub(u.last_used))),
+        ));
+    }
+
+    let lines: Vec<Line> = fields
+        .into_iter()
+        .map(|(label, value)| {
+            Line::from(vec![
+                Span::styled(format!(" {}: ", label), label_style),
+                Span::raw(value),
+            ])
+        })
+        .collect();
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(paragraph, area);
+}
+
+/// "just now", "5 minutes ago", "3 days ago", ...
+fn format_age(secs: u64) -> String {
+    let (amount, unit) = match secs {
+        0..60 => return "just now".to_string(),
+        60..3600 => (secs / 60, "minute"),
+        3600..86400 => (secs / 3600, "hour"),
+        86400..2592000 => (secs / 86400, "day"),
+        2592000..31536000 => (secs / 2592000, "month"),
+        _ => (secs / 31536000, "year"),
+    };
+    format!("{} {}{} ago", amount, unit, if amount == 1 { "" } else { "s" })
 }
@@ -2,42 +2,54 @@ use crate::ui::layout;
 use crate::app::{App, Focus};
 use crate::config::{DstlConfig, SearchPosition};
 use ratatui::Frame;
+use ratatui::text::Line;
 
 pub fn draw(
     f: &mut Frame,
-    app: &App,
-    _search_query: &str,
-    _apps: &[String],
+    app: &mut App,
     selected: usize,
     focus: Focus,
     search_position: SearchPosition,
     config: &DstlConfig,
 ) {
-    let chunks = layout::vertical_split(f, 3, search_position);
+    let (search_area, content_area) = layout::vertical_split(f, 3, search_position);
+    let (list_area, details_area) = layout::details_split(content_area, app, config);
+    if let Some(area) = details_area {
+        layout::render_details(f, area, app, config);
+    }
+
+    app.areas.search = search_area;
+    app.areas.categories = None;
+
+    if let Some(ref entry) = app.action_menu {
+        app.areas.apps = layout::render_action_menu(f, list_area, entry, app.selected_action, config);
+        layout::render_search_bar(f, search_area, &app.query, focus, app.vim_mode, config);
+        return;
+    }
     
-    let filtered_apps: Vec<String> = app
+    let query = app.search_query();
+    let filtered_apps: Vec<Line> = app
         .visible_apps()
         .into_iter()
-        .map(|a| a.name.clone())
+        .map(|a| layout::app_label(app, a, &query, config))
         .collect();
     
-    layout::render_list(
+    app.areas.apps = layout::render_list(
         f,
-        chunks.1,
-        " Apps ",
+        list_area,
+        layout::apps_title(app),
         &filtered_apps,
         selected,
         focus == Focus::Apps,
         config,
     );
     
-    // Pass cursor_position to render_search_bar
     layout::render_search_bar(
         f,
-        chunks.0,
-        &app.search_query,
-        app.cursor_position,
+        search_area,
+        &app.query,
         focus,
+        app.vim_mode,
         config,
     );
 }